@@ -0,0 +1,333 @@
+use state::State;
+
+/// Timer periods (in CPU cycles; NTSC)
+const RATE_TABLE: [u16; 16] = [428, 380, 340, 320, 286, 254, 226, 214, 190, 160, 142, 128, 106,
+                               84, 72, 54];
+
+/// Delta modulation channel (DMC)
+///
+/// Plays 1-bit delta-encoded samples that are fetched from $8000-$FFFF, one byte at a time,
+/// by DMA (which stalls the CPU). The output level can also be set directly from $4011.
+#[derive(Default)]
+pub struct DMC {
+    /// [$4010:7] IRQ enable
+    irq_enable: bool,
+
+    /// [$4010:6] Loop
+    looping: bool,
+
+    /// [$4010:0-3] Timer period (from the lookup table)
+    timer_period: u16,
+
+    /// Timer; counts down and clocks the output unit when it reaches zero
+    timer: u16,
+
+    /// [$4011:0-6] Output level (7 bits)
+    level: u8,
+
+    /// [$4012] Sample address (%11AAAAAA.AA000000)
+    sample_address: u16,
+
+    /// [$4013] Sample length (%LLLL.LLLL0001)
+    sample_length: u16,
+
+    /// Memory reader: address of the next byte to fetch
+    current_address: u16,
+
+    /// Memory reader: number of bytes left to fetch
+    bytes_remaining: u16,
+
+    /// Sample buffer (filled by DMA)
+    buffer: Option<u8>,
+
+    /// Output unit: shift register
+    shift: u8,
+
+    /// Output unit: number of bits left in the shift register
+    bits_remaining: u8,
+
+    /// Output unit: silence; set when the shift register was loaded from an empty buffer
+    silence: bool,
+
+    /// IRQ (signal); set when a sample ends (and IRQ is enabled)
+    pub irq_occurred: bool,
+}
+
+impl DMC {
+    pub fn new() -> DMC {
+        DMC {
+            timer_period: RATE_TABLE[0],
+            sample_address: 0xC000,
+            sample_length: 1,
+            bits_remaining: 8,
+            silence: true,
+            ..Default::default()
+        }
+    }
+
+    /// Write to one of the 4 registers of the channel (indexed from 0).
+    pub fn write(&mut self, index: u16, value: u8) {
+        match index {
+            0 => {
+                self.irq_enable = value & 0x80 != 0;
+                self.looping = value & 0x40 != 0;
+                self.timer_period = RATE_TABLE[(value & 0x0F) as usize];
+
+                if !self.irq_enable {
+                    self.irq_occurred = false;
+                }
+            }
+
+            1 => {
+                self.level = value & 0x7F;
+            }
+
+            2 => {
+                self.sample_address = 0xC000 | ((value as u16) << 6);
+            }
+
+            3 => {
+                self.sample_length = ((value as u16) << 4) | 1;
+            }
+
+            _ => unreachable!(),
+        }
+    }
+
+    /// Enable (or disable) the channel from $4015. Enabling restarts the sample only if
+    /// it has finished; disabling stops the sample (what is in the buffer still plays).
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.irq_occurred = false;
+
+        if !enabled {
+            self.bytes_remaining = 0;
+        } else if self.bytes_remaining == 0 {
+            self.restart();
+        }
+    }
+
+    pub fn active(&self) -> bool {
+        self.bytes_remaining > 0
+    }
+
+    fn restart(&mut self) {
+        self.current_address = self.sample_address;
+        self.bytes_remaining = self.sample_length;
+    }
+
+    /// Address of the next sample byte if the memory reader needs it; the fetch must be
+    /// completed (by the bus) with `fill`.
+    pub fn dma_request(&self) -> Option<u16> {
+        if self.buffer.is_none() && self.bytes_remaining > 0 {
+            Some(self.current_address)
+        } else {
+            None
+        }
+    }
+
+    /// Fill the sample buffer with the byte fetched by DMA.
+    pub fn fill(&mut self, value: u8) {
+        self.buffer = Some(value);
+
+        // Address wraps around to $8000 (not $0000)
+        self.current_address = self.current_address.wrapping_add(1) | 0x8000;
+        self.bytes_remaining -= 1;
+
+        if self.bytes_remaining == 0 {
+            if self.looping {
+                self.restart();
+            } else if self.irq_enable {
+                self.irq_occurred = true;
+            }
+        }
+    }
+
+    /// Clocked every CPU cycle
+    pub fn step_timer(&mut self) {
+        if self.timer == 0 {
+            self.timer = self.timer_period - 1;
+            self.step_output();
+        } else {
+            self.timer -= 1;
+        }
+    }
+
+    fn step_output(&mut self) {
+        if !self.silence {
+            // Bit 0 of the shift register moves the level by 2 (up on 1; down on 0);
+            // the level is left alone when that would take it out of 0-127
+            if self.shift & 1 != 0 {
+                if self.level <= 125 {
+                    self.level += 2;
+                }
+            } else if self.level >= 2 {
+                self.level -= 2;
+            }
+        }
+
+        self.shift >>= 1;
+        self.bits_remaining -= 1;
+
+        if self.bits_remaining == 0 {
+            // Start a new output cycle
+            self.bits_remaining = 8;
+
+            match self.buffer.take() {
+                Some(value) => {
+                    self.silence = false;
+                    self.shift = value;
+                }
+
+                None => {
+                    self.silence = true;
+                }
+            }
+        }
+    }
+
+    pub fn output(&self) -> u8 {
+        self.level
+    }
+
+    pub fn sync(&mut self, s: &mut State) {
+        s.bool(&mut self.irq_enable);
+        s.bool(&mut self.looping);
+        s.u16(&mut self.timer_period);
+        s.u16(&mut self.timer);
+        s.u8(&mut self.level);
+        s.u16(&mut self.sample_address);
+        s.u16(&mut self.sample_length);
+        s.u16(&mut self.current_address);
+        s.u16(&mut self.bytes_remaining);
+
+        let mut full = self.buffer.is_some();
+        let mut buffer = self.buffer.unwrap_or(0);
+        s.bool(&mut full);
+        s.u8(&mut buffer);
+        self.buffer = if full { Some(buffer) } else { None };
+
+        s.u8(&mut self.shift);
+        s.u8(&mut self.bits_remaining);
+        s.bool(&mut self.silence);
+        s.bool(&mut self.irq_occurred);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DMC;
+
+    /// DMC playing a sample of `length` ($4013) from `address` ($4012)
+    fn dmc(flags: u8, address: u8, length: u8) -> DMC {
+        let mut dmc = DMC::new();
+        dmc.write(0, flags);
+        dmc.write(2, address);
+        dmc.write(3, length);
+        dmc.set_enabled(true);
+
+        dmc
+    }
+
+    /// Fetch the next byte (by DMA) and empty the sample buffer again; the address fetched
+    fn fetch(dmc: &mut DMC) -> u16 {
+        let address = dmc.dma_request().unwrap();
+        dmc.fill(0);
+        dmc.buffer = None;
+
+        address
+    }
+
+    #[test]
+    fn memory_reader() {
+        // $C040 for 33 bytes; a full buffer is not fetched again
+        let mut dmc = dmc(0x00, 0x01, 0x02);
+        assert_eq!(dmc.dma_request(), Some(0xC040));
+        dmc.fill(0);
+        assert_eq!(dmc.dma_request(), None);
+        dmc.buffer = None;
+
+        for i in 1..33 {
+            assert_eq!(fetch(&mut dmc), 0xC040 + i);
+        }
+
+        assert!(!dmc.active());
+        assert_eq!(dmc.dma_request(), None);
+    }
+
+    #[test]
+    fn address_wraps() {
+        let mut dmc = dmc(0x00, 0xFF, 0x04);
+        for _ in 0..0x40 {
+            fetch(&mut dmc);
+        }
+
+        assert_eq!(fetch(&mut dmc), 0x8000);
+    }
+
+    #[test]
+    fn irq() {
+        let mut dmc = dmc(0x80, 0x00, 0x00);
+        fetch(&mut dmc);
+        assert!(dmc.irq_occurred);
+
+        // Cleared by a write to $4015 or by disabling the IRQ
+        dmc.set_enabled(true);
+        assert!(!dmc.irq_occurred);
+
+        fetch(&mut dmc);
+        dmc.write(0, 0x00);
+        assert!(!dmc.irq_occurred);
+    }
+
+    #[test]
+    fn looping() {
+        // The sample restarts (without an IRQ)
+        let mut dmc = dmc(0xC0, 0x00, 0x00);
+        assert_eq!(fetch(&mut dmc), 0xC000);
+        assert_eq!(fetch(&mut dmc), 0xC000);
+        assert!(!dmc.irq_occurred);
+        assert!(dmc.active());
+    }
+
+    #[test]
+    fn output() {
+        let cases = [(64, 0x0F, [66, 68, 70, 72, 70, 68, 66, 64]),
+                     (124, 0xFF, [126, 126, 126, 126, 126, 126, 126, 126]),
+                     (3, 0x00, [1, 1, 1, 1, 1, 1, 1, 1])];
+        for &(level, value, expected) in &cases {
+            let mut dmc = dmc(0x00, 0x00, 0x00);
+            dmc.write(1, level);
+            dmc.fill(value);
+
+            // The buffer is loaded at the end of the current (silent) output cycle
+            for _ in 0..8 {
+                dmc.step_output();
+            }
+            assert_eq!(dmc.output(), level);
+
+            let mut levels = [0; 8];
+            for i in 0..8 {
+                dmc.step_output();
+                levels[i] = dmc.output();
+            }
+
+            assert_eq!(levels, expected, "level {} and ${:02X}", level, value);
+        }
+    }
+
+    #[test]
+    fn timer() {
+        // Rate $F clocks the output unit every 54 CPU cycles
+        let mut dmc = dmc(0x0F, 0x00, 0x00);
+        dmc.fill(0xFF);
+
+        let mut cycles = 0;
+        while dmc.silence {
+            dmc.step_timer();
+            cycles += 1;
+        }
+
+        // 8 clocks; the first on the first cycle
+        assert_eq!(cycles, 7 * 54 + 1);
+    }
+}
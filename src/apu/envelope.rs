@@ -0,0 +1,104 @@
+use state::State;
+
+/// Envelope generator; either a constant volume or a decreasing saw envelope (which can
+/// loop). Clocked by the quarter-frame signal of the frame counter.
+#[derive(Default)]
+pub struct Envelope {
+    /// Set by a write to the 4th register of the channel; restarts the envelope
+    pub start: bool,
+
+    /// Loop (shared with the halt flag of the length counter)
+    pub looping: bool,
+
+    /// Constant volume (1) or envelope (0)
+    pub constant: bool,
+
+    /// Volume (constant) or period of the divider (envelope)
+    pub volume: u8,
+
+    /// Divider
+    divider: u8,
+
+    /// Decay level counter
+    decay: u8,
+}
+
+impl Envelope {
+    pub fn step(&mut self) {
+        if self.start {
+            self.start = false;
+            self.decay = 15;
+            self.divider = self.volume;
+        } else if self.divider == 0 {
+            self.divider = self.volume;
+
+            if self.decay > 0 {
+                self.decay -= 1;
+            } else if self.looping {
+                self.decay = 15;
+            }
+        } else {
+            self.divider -= 1;
+        }
+    }
+
+    pub fn output(&self) -> u8 {
+        if self.constant {
+            self.volume
+        } else {
+            self.decay
+        }
+    }
+
+    pub fn sync(&mut self, s: &mut State) {
+        s.bool(&mut self.start);
+        s.bool(&mut self.looping);
+        s.bool(&mut self.constant);
+        s.u8(&mut self.volume);
+        s.u8(&mut self.divider);
+        s.u8(&mut self.decay);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Envelope;
+
+    /// Output after each of `steps` steps (from a start)
+    fn outputs(envelope: &mut Envelope, steps: usize) -> Vec<u8> {
+        envelope.start = true;
+
+        (0..steps)
+            .map(|_| {
+                envelope.step();
+                envelope.output()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn decay() {
+        // A period of 1 decrements the level every 2 steps; the level stays at 0
+        let mut envelope = Envelope { volume: 1, ..Default::default() };
+        let outputs = outputs(&mut envelope, 34);
+
+        assert_eq!(&outputs[..6], &[15, 15, 14, 14, 13, 13]);
+        assert_eq!(&outputs[28..], &[1, 1, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn looping() {
+        let mut envelope = Envelope { volume: 0, looping: true, ..Default::default() };
+        let outputs = outputs(&mut envelope, 18);
+
+        assert_eq!(&outputs[14..], &[1, 0, 15, 14]);
+    }
+
+    #[test]
+    fn constant() {
+        let mut envelope = Envelope { volume: 7, constant: true, ..Default::default() };
+        let outputs = outputs(&mut envelope, 20);
+
+        assert!(outputs.iter().all(|&output| output == 7));
+    }
+}
@@ -0,0 +1,244 @@
+use state::State;
+
+/// Signals generated by the frame counter for a CPU cycle
+#[derive(Default)]
+pub struct Clocks {
+    /// Quarter frame (envelopes and the linear counter of the triangle)
+    pub quarter: bool,
+
+    /// Half frame (length counters and sweep units)
+    pub half: bool,
+}
+
+/// Frame counter (frame sequencer); generates the low-frequency clocks for the channels
+/// and (in 4-step mode) the frame IRQ.
+///
+/// Step    4-step mode         5-step mode
+/// ----    -----------         -----------
+/// 7457    quarter             quarter
+/// 14913   quarter, half       quarter, half
+/// 22371   quarter             quarter
+/// 29829   quarter, half, IRQ  -
+/// 37281   -                   quarter, half
+///
+/// NOTE: Steps are in CPU cycles; the frame IRQ flag is set for 3 cycles in a row
+///       (29828 to 29830) and the sequence wraps around at 29830 (4-step) or 37282 (5-step).
+#[derive(Default)]
+pub struct FrameCounter {
+    /// [$4017:7] Mode (0: 4-step; 1: 5-step)
+    five_step: bool,
+
+    /// [$4017:6] IRQ inhibit
+    irq_inhibit: bool,
+
+    /// IRQ (signal); held until acknowledged by a read of $4015 (or inhibited)
+    pub irq_occurred: bool,
+
+    /// Number of CPU cycles into the sequence
+    cycles: u32,
+
+    /// Number of CPU cycles until the sequence is reset after a write to $4017 (0 if
+    /// no reset is pending)
+    reset_delay: u8,
+}
+
+impl FrameCounter {
+    /// Write to $4017; `odd` is the parity of the CPU cycle of the write.
+    pub fn write(&mut self, value: u8, odd: bool) {
+        self.five_step = value & 0x80 != 0;
+        self.irq_inhibit = value & 0x40 != 0;
+
+        if self.irq_inhibit {
+            self.irq_occurred = false;
+        }
+
+        // The sequence is reset 3 or 4 CPU cycles after the write (depending on whether
+        // the write lands on an APU cycle or between APU cycles)
+        self.reset_delay = if odd { 4 } else { 3 };
+    }
+
+    /// Reset (soft); the sequence restarts as if $4017 was written again with the same mode
+    /// and the frame IRQ is acknowledged.
+    pub fn soft_reset(&mut self, odd: bool) {
+        let value = ((self.five_step as u8) << 7) | ((self.irq_inhibit as u8) << 6);
+        self.write(value, odd);
+
+        self.irq_occurred = false;
+    }
+
+    /// Clocked every CPU cycle
+    pub fn step(&mut self) -> Clocks {
+        let mut clocks = Clocks::default();
+
+        self.cycles += 1;
+
+        match self.cycles {
+            7457 | 22371 => {
+                clocks.quarter = true;
+            }
+
+            14913 => {
+                clocks.quarter = true;
+                clocks.half = true;
+            }
+
+            29828 if !self.five_step => {
+                self.set_irq();
+            }
+
+            29829 if !self.five_step => {
+                clocks.quarter = true;
+                clocks.half = true;
+
+                self.set_irq();
+            }
+
+            29830 if !self.five_step => {
+                self.set_irq();
+
+                self.cycles = 0;
+            }
+
+            37281 => {
+                clocks.quarter = true;
+                clocks.half = true;
+            }
+
+            37282 => {
+                self.cycles = 0;
+            }
+
+            _ => {}
+        }
+
+        if self.reset_delay > 0 {
+            self.reset_delay -= 1;
+
+            if self.reset_delay == 0 {
+                self.cycles = 0;
+
+                // Entering 5-step mode immediately clocks the quarter- and half-frame units
+                if self.five_step {
+                    clocks.quarter = true;
+                    clocks.half = true;
+                }
+            }
+        }
+
+        clocks
+    }
+
+    fn set_irq(&mut self) {
+        if !self.irq_inhibit {
+            self.irq_occurred = true;
+        }
+    }
+
+    pub fn sync(&mut self, s: &mut State) {
+        s.bool(&mut self.five_step);
+        s.bool(&mut self.irq_inhibit);
+        s.bool(&mut self.irq_occurred);
+        s.u32(&mut self.cycles);
+        s.u8(&mut self.reset_delay);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FrameCounter;
+
+    /// Cycles (counted from 1) within the next `cycles` that clock the quarter- and
+    /// half-frame units
+    fn clocks(frame: &mut FrameCounter, cycles: u32) -> (Vec<u32>, Vec<u32>) {
+        let mut quarter = Vec::new();
+        let mut half = Vec::new();
+        for cycle in 1..(cycles + 1) {
+            let clocks = frame.step();
+            if clocks.quarter {
+                quarter.push(cycle);
+            }
+
+            if clocks.half {
+                half.push(cycle);
+            }
+        }
+
+        (quarter, half)
+    }
+
+    #[test]
+    fn four_step() {
+        let mut frame: FrameCounter = Default::default();
+        let (quarter, half) = clocks(&mut frame, 29830 + 7457);
+
+        assert_eq!(quarter, [7457, 14913, 22371, 29829, 29830 + 7457]);
+        assert_eq!(half, [14913, 29829]);
+    }
+
+    #[test]
+    fn five_step() {
+        // The sequence restarts 3 cycles after the write (on an even cycle) and clocks
+        // both units at once
+        let mut frame: FrameCounter = Default::default();
+        frame.write(0x80, false);
+        let (quarter, half) = clocks(&mut frame, 3 + 37282 + 7457);
+
+        assert_eq!(quarter, [3, 3 + 7457, 3 + 14913, 3 + 22371, 3 + 37281, 3 + 37282 + 7457]);
+        assert_eq!(half, [3, 3 + 14913, 3 + 37281]);
+        assert!(!frame.irq_occurred);
+    }
+
+    #[test]
+    fn reset_delay() {
+        // 4 cycles after a write on an odd cycle
+        let mut frame: FrameCounter = Default::default();
+        frame.write(0x80, true);
+        let (quarter, _) = clocks(&mut frame, 10);
+
+        assert_eq!(quarter, [4]);
+    }
+
+    #[test]
+    fn irq() {
+        // Set on the last 3 cycles of the 4-step sequence
+        let mut frame: FrameCounter = Default::default();
+        for cycle in 1..29831 {
+            frame.step();
+
+            if cycle == 29827 {
+                assert!(!frame.irq_occurred);
+            } else if cycle == 29828 {
+                assert!(frame.irq_occurred);
+            }
+        }
+
+        // Held until acknowledged (the flag is cleared by the APU on a read of $4015)
+        frame.step();
+        assert!(frame.irq_occurred);
+        frame.irq_occurred = false;
+
+        // Set again on the next pass through 29828 to 29830
+        for _ in 0..29828 {
+            frame.step();
+        }
+        assert!(frame.irq_occurred);
+    }
+
+    #[test]
+    fn irq_inhibit() {
+        let mut frame: FrameCounter = Default::default();
+        for _ in 0..29830 {
+            frame.step();
+        }
+        assert!(frame.irq_occurred);
+
+        // Setting inhibit acknowledges the IRQ (and no more are set)
+        frame.write(0x40, false);
+        assert!(!frame.irq_occurred);
+
+        for _ in 0..(2 * 29830) {
+            frame.step();
+        }
+        assert!(!frame.irq_occurred);
+    }
+}
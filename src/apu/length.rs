@@ -0,0 +1,119 @@
+use state::State;
+
+/// Lookup table of length counter values (indexed by bits 3-7 of the 4th register of
+/// each channel)
+const LENGTH_TABLE: [u8; 32] = [10, 254, 20, 2, 40, 4, 80, 6, 160, 8, 60, 10, 14, 12, 26, 14,
+                                12, 16, 24, 18, 48, 20, 96, 22, 192, 24, 72, 26, 16, 28, 32, 30];
+
+/// Length counter; silences the channel when it reaches zero. Clocked by the half-frame
+/// signal of the frame counter.
+#[derive(Default)]
+pub struct LengthCounter {
+    /// Enabled from $4015; when disabled the counter is held at zero
+    enabled: bool,
+
+    /// Halt; when set the counter is not clocked
+    pub halt: bool,
+
+    /// Counter
+    counter: u8,
+}
+
+impl LengthCounter {
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+
+        if !enabled {
+            self.counter = 0;
+        }
+    }
+
+    /// Load the counter from the lookup table (if enabled).
+    pub fn load(&mut self, index: u8) {
+        if self.enabled {
+            self.counter = LENGTH_TABLE[(index & 0x1F) as usize];
+        }
+    }
+
+    pub fn step(&mut self) {
+        if !self.halt && self.counter > 0 {
+            self.counter -= 1;
+        }
+    }
+
+    pub fn active(&self) -> bool {
+        self.counter > 0
+    }
+
+    pub fn sync(&mut self, s: &mut State) {
+        s.bool(&mut self.enabled);
+        s.bool(&mut self.halt);
+        s.u8(&mut self.counter);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LengthCounter;
+
+    /// Number of steps until the counter (loaded from `index`) silences the channel
+    fn steps(index: u8) -> usize {
+        let mut length: LengthCounter = Default::default();
+        length.set_enabled(true);
+        length.load(index);
+
+        let mut steps = 0;
+        while length.active() {
+            length.step();
+            steps += 1;
+        }
+
+        steps
+    }
+
+    #[test]
+    fn table() {
+        let cases = [(0x00, 10), (0x01, 254), (0x02, 20), (0x03, 2), (0x08, 160), (0x18, 192),
+                     (0x1E, 32), (0x1F, 30)];
+        for &(index, expected) in &cases {
+            assert_eq!(steps(index), expected, "index ${:02X}", index);
+        }
+
+        // Only bits 0-4 of the index are used
+        assert_eq!(steps(0x21), 254);
+    }
+
+    #[test]
+    fn halt() {
+        let mut length: LengthCounter = Default::default();
+        length.set_enabled(true);
+        length.load(0x03);
+
+        length.halt = true;
+        for _ in 0..10 {
+            length.step();
+        }
+        assert!(length.active());
+
+        length.halt = false;
+        length.step();
+        length.step();
+        assert!(!length.active());
+    }
+
+    #[test]
+    fn disabled() {
+        let mut length: LengthCounter = Default::default();
+
+        // Loads are ignored while disabled; disabling clears the counter
+        length.load(0x01);
+        assert!(!length.active());
+
+        length.set_enabled(true);
+        length.load(0x01);
+        assert!(length.active());
+
+        length.set_enabled(false);
+        assert!(!length.active());
+    }
+}
@@ -0,0 +1,92 @@
+/// Mixer; combines the outputs of the channels with the nonlinear response of the DACs
+/// of the 2A03 (approximated with lookup tables).
+///
+/// pulse_out = 95.88 / (8128 / (pulse_1 + pulse_2) + 100)
+/// tnd_out = 159.79 / (1 / (triangle / 8227 + noise / 12241 + dmc / 22638) + 100)
+///
+/// The tables are indexed by `pulse_1 + pulse_2` and `3 * triangle + 2 * noise + dmc` (the
+/// usual approximation of the second formula).
+pub struct Mixer {
+    /// Pulse table (31 entries)
+    pulse_table: Vec<f32>,
+
+    /// Triangle, noise, and DMC table (203 entries)
+    tnd_table: Vec<f32>,
+}
+
+impl Mixer {
+    pub fn new() -> Mixer {
+        let pulse_table = (0..31)
+            .map(|n| if n == 0 { 0.0 } else { 95.52 / (8128.0 / n as f32 + 100.0) })
+            .collect();
+
+        let tnd_table = (0..203)
+            .map(|n| if n == 0 { 0.0 } else { 163.67 / (24329.0 / n as f32 + 100.0) })
+            .collect();
+
+        Mixer {
+            pulse_table: pulse_table,
+            tnd_table: tnd_table,
+        }
+    }
+
+    /// Mix the channel outputs (pulse, triangle, and noise are 0-15; DMC is 0-127) into
+    /// an amplitude (0.0-1.0).
+    pub fn mix(&self, pulse_1: u8, pulse_2: u8, triangle: u8, noise: u8, dmc: u8) -> f32 {
+        let pulse = (pulse_1 + pulse_2) as usize;
+        let tnd = 3 * triangle as usize + 2 * noise as usize + dmc as usize;
+
+        self.pulse_table[pulse] + self.tnd_table[tnd]
+    }
+}
+
+impl Default for Mixer {
+    fn default() -> Mixer {
+        Mixer::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Mixer;
+
+    fn close(a: f32, b: f32) -> bool {
+        (a - b).abs() < 0.0005
+    }
+
+    #[test]
+    fn silence() {
+        assert_eq!(Mixer::new().mix(0, 0, 0, 0, 0), 0.0);
+    }
+
+    #[test]
+    fn levels() {
+        let mixer = Mixer::new();
+
+        // pulse_out = 95.52 / (8128 / n + 100); tnd_out = 163.67 / (24329 / n + 100)
+        assert!(close(mixer.mix(15, 0, 0, 0, 0), 0.1488));
+        assert!(close(mixer.mix(15, 15, 0, 0, 0), 0.2575));
+        assert!(close(mixer.mix(0, 0, 15, 0, 0), 0.2555));
+        assert!(close(mixer.mix(0, 0, 0, 15, 0), 0.1797));
+        assert!(close(mixer.mix(0, 0, 0, 0, 127), 0.5613));
+
+        // Both pulse channels share a DAC (as do the other three channels)
+        assert_eq!(mixer.mix(5, 10, 0, 0, 0), mixer.mix(10, 5, 0, 0, 0));
+        assert_eq!(mixer.mix(0, 0, 2, 0, 0), mixer.mix(0, 0, 0, 3, 0));
+
+        // All channels at their maximum is (close to) full scale
+        assert!((mixer.mix(15, 15, 15, 15, 127) - 1.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn monotonic() {
+        let mixer = Mixer::new();
+        for n in 0..30 {
+            assert!(mixer.pulse_table[n] < mixer.pulse_table[n + 1]);
+        }
+
+        for n in 0..202 {
+            assert!(mixer.tnd_table[n] < mixer.tnd_table[n + 1]);
+        }
+    }
+}
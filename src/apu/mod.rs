@@ -0,0 +1,333 @@
+mod envelope;
+mod length;
+mod pulse;
+mod triangle;
+mod noise;
+mod dmc;
+mod frame;
+mod mixer;
+mod resampler;
+
+use self::pulse::Pulse;
+use self::triangle::Triangle;
+use self::noise::Noise;
+use self::dmc::DMC;
+use self::frame::FrameCounter;
+use self::mixer::Mixer;
+use self::resampler::Resampler;
+use state::State;
+
+/// Clock rate of the CPU (and the rate at which the APU is stepped; NTSC)
+pub const CLOCK_RATE: f64 = 1789773.0;
+
+/// Default output sample rate (in Hz)
+pub const SAMPLE_RATE: u32 = 44100;
+
+/// Number of samples to collect before they are handed to the callback
+const SAMPLE_BATCH: usize = 512;
+
+/// Audio samples; handed to the samples callback in batches
+pub struct Samples<'a> {
+    /// Sample data (mono; centered around 0.0)
+    pub data: &'a [f32],
+
+    /// Sample rate (in Hz)
+    pub rate: u32,
+}
+
+#[derive(Default)]
+pub struct APU {
+    /// Channel: Pulse 1 ($4000-$4003)
+    pulse_1: Pulse,
+
+    /// Channel: Pulse 2 ($4004-$4007)
+    pulse_2: Pulse,
+
+    /// Channel: Triangle ($4008-$400B)
+    triangle: Triangle,
+
+    /// Channel: Noise ($400C-$400F)
+    noise: Noise,
+
+    /// Channel: DMC ($4010-$4013)
+    dmc: DMC,
+
+    /// Frame counter ($4017)
+    frame: FrameCounter,
+
+    /// Parity of the current CPU cycle; an APU cycle is every other CPU cycle
+    odd: bool,
+
+    /// Callback: Samples (once a batch of samples is ready)
+    on_samples: Option<Box<FnMut(Samples) -> ()>>,
+
+    /// Mixer
+    mixer: Mixer,
+
+    /// Resampler (from the CPU clock to the output sample rate)
+    resampler: Option<Resampler>,
+
+    /// Output sample rate (in Hz; 0 until set or reset)
+    sample_rate: u32,
+
+    /// Samples waiting to be handed to the callback
+    samples: Vec<f32>,
+
+    /// Keep the samples that are handed to the callback in the capture buffer
+    capture: bool,
+
+    /// Capture buffer
+    captured: Vec<f32>,
+}
+
+impl APU {
+    pub fn set_on_samples(&mut self, callback: Box<FnMut(Samples) -> ()>) {
+        self.on_samples = Some(callback);
+    }
+
+    /// Remove the samples callback (to be set again later)
+    pub fn take_on_samples(&mut self) -> Option<Box<FnMut(Samples) -> ()>> {
+        self.on_samples.take()
+    }
+
+    pub fn set_sample_rate(&mut self, rate: u32) {
+        self.sample_rate = rate;
+        self.resampler = Some(Resampler::new(CLOCK_RATE, rate));
+        self.samples.clear();
+    }
+
+    pub fn reset(&mut self) {
+        self.pulse_1 = Pulse::new(true);
+        self.pulse_2 = Pulse::new(false);
+        self.triangle = Default::default();
+        self.noise = Noise::new();
+        self.dmc = DMC::new();
+
+        // NOTE: The frame counter acts as if $4017 was written with $00 on reset
+        self.frame = Default::default();
+        self.odd = false;
+
+        let rate = if self.sample_rate == 0 { SAMPLE_RATE } else { self.sample_rate };
+        self.set_sample_rate(rate);
+    }
+
+    /// Reset (soft); silences all channels (as a write of $00 to $4015) and restarts the
+    /// frame counter
+    pub fn soft_reset(&mut self) {
+        self.write(0x4015, 0);
+        self.frame.soft_reset(self.odd);
+    }
+
+    pub fn step(&mut self) {
+        self.triangle.step_timer();
+        self.noise.step_timer();
+        self.dmc.step_timer();
+
+        // Pulse timers are clocked every APU cycle (every other CPU cycle)
+        if self.odd {
+            self.pulse_1.step_timer();
+            self.pulse_2.step_timer();
+        }
+
+        self.odd = !self.odd;
+
+        let clocks = self.frame.step();
+
+        if clocks.quarter {
+            self.step_quarter_frame();
+        }
+
+        if clocks.half {
+            self.step_half_frame();
+        }
+
+        self.step_output();
+    }
+
+    /// Mix the output of the channels and resample it; hand the samples to the callback
+    /// once a batch is ready
+    fn step_output(&mut self) {
+        let amplitude = self.mixer.mix(self.pulse_1.output(),
+                                       self.pulse_2.output(),
+                                       self.triangle.output(),
+                                       self.noise.output(),
+                                       self.dmc.output());
+
+        let available = match self.resampler {
+            Some(ref mut resampler) => {
+                resampler.add(amplitude);
+                resampler.available()
+            }
+
+            None => 0,
+        };
+
+        if available >= SAMPLE_BATCH {
+            self.flush();
+        }
+    }
+
+    /// Hand the samples that are ready to the callback (and to the capture buffer)
+    pub fn flush(&mut self) {
+        if let Some(ref mut resampler) = self.resampler {
+            resampler.read(&mut self.samples);
+
+            if self.samples.is_empty() {
+                return;
+            }
+
+            if let Some(ref mut on_samples) = self.on_samples {
+                (on_samples)(Samples {
+                    data: &self.samples,
+                    rate: resampler.rate,
+                });
+            }
+
+            if self.capture {
+                self.captured.extend_from_slice(&self.samples);
+            }
+
+            self.samples.clear();
+        }
+    }
+
+    /// Start (or stop) keeping the samples in the capture buffer; starting clears it
+    pub fn set_capture(&mut self, capture: bool) {
+        if capture {
+            self.captured.clear();
+        }
+
+        self.capture = capture;
+    }
+
+    /// Samples kept in the capture buffer
+    pub fn captured<'a>(&'a self) -> Samples<'a> {
+        Samples {
+            data: &self.captured,
+            rate: self.sample_rate,
+        }
+    }
+
+    /// IRQ (signal)
+    pub fn irq(&self) -> bool {
+        self.frame.irq_occurred || self.dmc.irq_occurred
+    }
+
+    /// Address of the next DMC sample byte if a DMA fetch is pending
+    pub fn dma_request(&self) -> Option<u16> {
+        self.dmc.dma_request()
+    }
+
+    /// Complete a pending DMA fetch
+    pub fn dma_fill(&mut self, value: u8) {
+        self.dmc.fill(value);
+    }
+
+    /// DMA reads can only happen on every other CPU cycle ("get" cycles; as opposed to
+    /// "put" cycles) which are fixed relative to the APU clock
+    pub fn get_cycle(&self) -> bool {
+        self.odd
+    }
+
+    /// Quarter frame: clock envelopes and the linear counter
+    fn step_quarter_frame(&mut self) {
+        self.pulse_1.envelope.step();
+        self.pulse_2.envelope.step();
+        self.noise.envelope.step();
+
+        self.triangle.step_linear();
+    }
+
+    /// Half frame: clock length counters and sweep units
+    fn step_half_frame(&mut self) {
+        self.pulse_1.length.step();
+        self.pulse_2.length.step();
+        self.triangle.length.step();
+        self.noise.length.step();
+
+        self.pulse_1.step_sweep();
+        self.pulse_2.step_sweep();
+    }
+
+    pub fn read(&mut self, address: u16) -> u8 {
+        match address {
+            // Status: length counter of each channel is > 0 (DMC: bytes remaining > 0)
+            // and frame and DMC interrupts; acknowledges the frame interrupt
+            0x4015 => {
+                let frame_irq = self.frame.irq_occurred;
+                self.frame.irq_occurred = false;
+
+                (self.pulse_1.length.active() as u8) |
+                ((self.pulse_2.length.active() as u8) << 1) |
+                ((self.triangle.length.active() as u8) << 2) |
+                ((self.noise.length.active() as u8) << 3) |
+                ((self.dmc.active() as u8) << 4) |
+                ((frame_irq as u8) << 6) |
+                ((self.dmc.irq_occurred as u8) << 7)
+            }
+
+            _ => {
+                // warn!("unhandled read at ${:04X}", address);
+
+                0
+            }
+        }
+    }
+
+    pub fn write(&mut self, address: u16, value: u8) {
+        match address {
+            0x4000...0x4003 => {
+                self.pulse_1.write(address - 0x4000, value);
+            }
+
+            0x4004...0x4007 => {
+                self.pulse_2.write(address - 0x4004, value);
+            }
+
+            0x4008...0x400B => {
+                self.triangle.write(address - 0x4008, value);
+            }
+
+            0x400C...0x400F => {
+                self.noise.write(address - 0x400C, value);
+            }
+
+            0x4010...0x4013 => {
+                self.dmc.write(address - 0x4010, value);
+            }
+
+            // Status: enable each channel (and acknowledge the DMC interrupt)
+            0x4015 => {
+                self.pulse_1.length.set_enabled(value & 0x01 != 0);
+                self.pulse_2.length.set_enabled(value & 0x02 != 0);
+                self.triangle.length.set_enabled(value & 0x04 != 0);
+                self.noise.length.set_enabled(value & 0x08 != 0);
+                self.dmc.set_enabled(value & 0x10 != 0);
+            }
+
+            // Frame counter
+            0x4017 => {
+                self.frame.write(value, self.odd);
+            }
+
+            _ => {
+                // warn!("unhandled write at ${:04X} with ${:02X} ({})",
+                //       address,
+                //       value,
+                //       value);
+            }
+        }
+    }
+
+    /// Save (or load) the state of the channels and the frame counter; pending output
+    /// samples are not part of the state.
+    pub fn sync(&mut self, s: &mut State) {
+        self.pulse_1.sync(s);
+        self.pulse_2.sync(s);
+        self.triangle.sync(s);
+        self.noise.sync(s);
+        self.dmc.sync(s);
+        self.frame.sync(s);
+        s.bool(&mut self.odd);
+    }
+}
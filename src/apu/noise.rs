@@ -0,0 +1,160 @@
+use super::envelope::Envelope;
+use super::length::LengthCounter;
+use state::State;
+
+/// Timer periods (in CPU cycles; NTSC)
+const PERIOD_TABLE: [u16; 16] = [4, 8, 16, 32, 64, 96, 128, 160, 202, 254, 380, 508, 762, 1016,
+                                 2034, 4068];
+
+/// Noise channel
+#[derive(Default)]
+pub struct Noise {
+    /// [$400E:7] Mode; feedback from bit 6 (1; short, "metallic" sequence) or
+    /// bit 1 (0; long sequence) of the shift register
+    mode: bool,
+
+    /// [$400E:0-3] Timer period (from the lookup table)
+    timer_period: u16,
+
+    /// Timer; counts down and clocks the shift register when it reaches zero
+    timer: u16,
+
+    /// Linear-feedback shift register (15 bits)
+    shift: u16,
+
+    pub envelope: Envelope,
+    pub length: LengthCounter,
+}
+
+impl Noise {
+    pub fn new() -> Noise {
+        // The shift register is loaded with 1 on power-up
+        Noise { shift: 1, timer_period: PERIOD_TABLE[0], ..Default::default() }
+    }
+
+    /// Write to one of the 4 registers of the channel (indexed from 0).
+    pub fn write(&mut self, index: u16, value: u8) {
+        match index {
+            0 => {
+                self.length.halt = value & 0x20 != 0;
+                self.envelope.looping = value & 0x20 != 0;
+                self.envelope.constant = value & 0x10 != 0;
+                self.envelope.volume = value & 0x0F;
+            }
+
+            // Unused
+            1 => {}
+
+            2 => {
+                self.mode = value & 0x80 != 0;
+                self.timer_period = PERIOD_TABLE[(value & 0x0F) as usize];
+            }
+
+            3 => {
+                self.length.load(value >> 3);
+                self.envelope.start = true;
+            }
+
+            _ => unreachable!(),
+        }
+    }
+
+    /// Clocked every CPU cycle
+    pub fn step_timer(&mut self) {
+        if self.timer == 0 {
+            self.timer = self.timer_period - 1;
+
+            let tap = if self.mode { 6 } else { 1 };
+            let feedback = (self.shift ^ (self.shift >> tap)) & 1;
+
+            self.shift = (self.shift >> 1) | (feedback << 14);
+        } else {
+            self.timer -= 1;
+        }
+    }
+
+    pub fn output(&self) -> u8 {
+        if !self.length.active() || self.shift & 1 != 0 {
+            0
+        } else {
+            self.envelope.output()
+        }
+    }
+
+    pub fn sync(&mut self, s: &mut State) {
+        s.bool(&mut self.mode);
+        s.u16(&mut self.timer_period);
+        s.u16(&mut self.timer);
+        s.u16(&mut self.shift);
+
+        self.envelope.sync(s);
+        self.length.sync(s);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Noise;
+
+    /// Steps of the shift register until it is back at its power-up value
+    fn sequence_length(mode: u8) -> usize {
+        let mut noise = Noise::new();
+        noise.write(2, mode);
+
+        let mut steps = 0;
+        loop {
+            // Period index 0 clocks the shift register every 4 CPU cycles
+            for _ in 0..4 {
+                noise.step_timer();
+            }
+
+            steps += 1;
+            if noise.shift == 1 {
+                return steps;
+            }
+        }
+    }
+
+    #[test]
+    fn sequence() {
+        assert_eq!(sequence_length(0x00), 32767);
+        assert_eq!(sequence_length(0x80), 93);
+    }
+
+    #[test]
+    fn timer() {
+        // The shift register is clocked once every period (in CPU cycles)
+        for &(index, period) in &[(0x0, 4), (0x4, 64), (0xF, 4068)] {
+            let mut noise = Noise::new();
+            noise.write(2, index);
+
+            // The timer starts at 0 (clocked on the first step)
+            noise.step_timer();
+
+            let shift = noise.shift;
+            for _ in 0..(period - 1) {
+                noise.step_timer();
+            }
+            assert_eq!(noise.shift, shift, "index {}", index);
+
+            noise.step_timer();
+            assert!(noise.shift != shift, "index {}", index);
+        }
+    }
+
+    #[test]
+    fn output() {
+        // Silent while bit 0 of the shift register is set (or the length counter is 0)
+        let mut noise = Noise::new();
+        noise.length.set_enabled(true);
+        noise.write(0, 0x19);
+        noise.write(3, 0x08);
+        assert_eq!(noise.output(), 0);
+
+        noise.shift = 2;
+        assert_eq!(noise.output(), 9);
+
+        noise.length.set_enabled(false);
+        assert_eq!(noise.output(), 0);
+    }
+}
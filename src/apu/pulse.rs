@@ -0,0 +1,243 @@
+use super::envelope::Envelope;
+use super::length::LengthCounter;
+use state::State;
+
+/// Duty cycle sequences (12.5%, 25%, 50%, and 25% negated)
+const DUTY_TABLE: [[u8; 8]; 4] = [[0, 1, 0, 0, 0, 0, 0, 0],
+                                  [0, 1, 1, 0, 0, 0, 0, 0],
+                                  [0, 1, 1, 1, 1, 0, 0, 0],
+                                  [1, 0, 0, 1, 1, 1, 1, 1]];
+
+/// Pulse (square wave) channel
+#[derive(Default)]
+pub struct Pulse {
+    /// Channel 1 negates the sweep change with one's complement (instead of two's
+    /// complement like channel 2); this makes its target period 1 lower
+    ones_complement: bool,
+
+    /// [$4000:6-7] Duty cycle
+    duty: u8,
+
+    /// Position in the duty cycle sequence
+    sequence: u8,
+
+    /// [$4002, $4003:0-2] Timer period (11 bits)
+    timer_period: u16,
+
+    /// Timer; counts down and steps the sequencer when it reaches zero
+    timer: u16,
+
+    /// [$4001:7] Sweep enable
+    sweep_enable: bool,
+
+    /// [$4001:4-6] Sweep divider period
+    sweep_period: u8,
+
+    /// [$4001:3] Sweep negate
+    sweep_negate: bool,
+
+    /// [$4001:0-2] Sweep shift count
+    sweep_shift: u8,
+
+    /// Set by a write to $4001; reloads the sweep divider on the next clock
+    sweep_reload: bool,
+
+    /// Sweep divider
+    sweep_divider: u8,
+
+    pub envelope: Envelope,
+    pub length: LengthCounter,
+}
+
+impl Pulse {
+    pub fn new(ones_complement: bool) -> Pulse {
+        Pulse { ones_complement: ones_complement, ..Default::default() }
+    }
+
+    /// Write to one of the 4 registers of the channel (indexed from 0).
+    pub fn write(&mut self, index: u16, value: u8) {
+        match index {
+            0 => {
+                self.duty = value >> 6;
+                self.length.halt = value & 0x20 != 0;
+                self.envelope.looping = value & 0x20 != 0;
+                self.envelope.constant = value & 0x10 != 0;
+                self.envelope.volume = value & 0x0F;
+            }
+
+            1 => {
+                self.sweep_enable = value & 0x80 != 0;
+                self.sweep_period = (value >> 4) & 7;
+                self.sweep_negate = value & 0x08 != 0;
+                self.sweep_shift = value & 7;
+                self.sweep_reload = true;
+            }
+
+            2 => {
+                self.timer_period = (self.timer_period & 0x700) | value as u16;
+            }
+
+            3 => {
+                self.timer_period = (self.timer_period & 0xFF) | ((value as u16 & 7) << 8);
+                self.length.load(value >> 3);
+
+                // Restart the sequencer and the envelope
+                self.sequence = 0;
+                self.envelope.start = true;
+            }
+
+            _ => unreachable!(),
+        }
+    }
+
+    /// Clocked every APU cycle (every other CPU cycle)
+    pub fn step_timer(&mut self) {
+        if self.timer == 0 {
+            self.timer = self.timer_period;
+            self.sequence = (self.sequence + 1) & 7;
+        } else {
+            self.timer -= 1;
+        }
+    }
+
+    /// Clocked by the half-frame signal of the frame counter
+    pub fn step_sweep(&mut self) {
+        if self.sweep_divider == 0 && self.sweep_enable && self.sweep_shift > 0 &&
+           !self.sweep_muted() {
+            self.timer_period = self.sweep_target();
+        }
+
+        if self.sweep_divider == 0 || self.sweep_reload {
+            self.sweep_divider = self.sweep_period;
+            self.sweep_reload = false;
+        } else {
+            self.sweep_divider -= 1;
+        }
+    }
+
+    fn sweep_target(&self) -> u16 {
+        let change = self.timer_period >> self.sweep_shift;
+        if !self.sweep_negate {
+            self.timer_period + change
+        } else if self.ones_complement {
+            self.timer_period.saturating_sub(change + 1)
+        } else {
+            self.timer_period.saturating_sub(change)
+        }
+    }
+
+    /// The sweep unit mutes the channel when the period is too low (< 8) or the target
+    /// period overflows (> $7FF); even when the sweep is disabled
+    fn sweep_muted(&self) -> bool {
+        self.timer_period < 8 || self.sweep_target() > 0x7FF
+    }
+
+    pub fn output(&self) -> u8 {
+        if !self.length.active() || self.sweep_muted() ||
+           DUTY_TABLE[self.duty as usize][self.sequence as usize] == 0 {
+            0
+        } else {
+            self.envelope.output()
+        }
+    }
+
+    pub fn sync(&mut self, s: &mut State) {
+        s.u8(&mut self.duty);
+        s.u8(&mut self.sequence);
+        s.u16(&mut self.timer_period);
+        s.u16(&mut self.timer);
+        s.bool(&mut self.sweep_enable);
+        s.u8(&mut self.sweep_period);
+        s.bool(&mut self.sweep_negate);
+        s.u8(&mut self.sweep_shift);
+        s.bool(&mut self.sweep_reload);
+        s.u8(&mut self.sweep_divider);
+
+        self.envelope.sync(s);
+        self.length.sync(s);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Pulse;
+
+    /// Pulse with a constant volume of 5, the length counter loaded, and a timer period of
+    /// `period`
+    fn pulse(ones_complement: bool, duty: u8, period: u16) -> Pulse {
+        let mut pulse = Pulse::new(ones_complement);
+        pulse.length.set_enabled(true);
+
+        pulse.write(0, (duty << 6) | 0x15);
+        pulse.write(2, period as u8);
+        pulse.write(3, 0x08 | (period >> 8) as u8);
+
+        pulse
+    }
+
+    #[test]
+    fn duty() {
+        let cases = [(0, [0, 5, 0, 0, 0, 0, 0, 0]),
+                     (1, [0, 5, 5, 0, 0, 0, 0, 0]),
+                     (2, [0, 5, 5, 5, 5, 0, 0, 0]),
+                     (3, [5, 0, 0, 5, 5, 5, 5, 5])];
+        for &(duty, expected) in &cases {
+            let mut pulse = pulse(false, duty, 8);
+
+            // The sequence steps each time the timer reaches 0 (every period + 1 steps)
+            let mut outputs = [0; 8];
+            for i in 0..8 {
+                outputs[i] = pulse.output();
+                for _ in 0..9 {
+                    pulse.step_timer();
+                }
+            }
+
+            assert_eq!(outputs, expected, "duty {}", duty);
+        }
+    }
+
+    #[test]
+    fn sweep() {
+        // Channel 1 negates with one's complement; channel 2 with two's complement
+        let cases = [(false, 0x81, 0x180), (false, 0x89, 0x080), (true, 0x89, 0x07F),
+                     (true, 0x82, 0x140)];
+        for &(ones_complement, sweep, expected) in &cases {
+            let mut pulse = pulse(ones_complement, 2, 0x100);
+            pulse.write(1, sweep);
+            pulse.step_sweep();
+
+            assert_eq!(pulse.timer_period, expected, "sweep ${:02X}", sweep);
+        }
+    }
+
+    #[test]
+    fn sweep_divider() {
+        // A divider period of 2 updates the period every 3 half frames (starting with the
+        // first, as the divider is at 0)
+        let mut pulse = pulse(false, 2, 0x100);
+        pulse.write(1, 0xA3);
+
+        let mut periods = Vec::new();
+        for _ in 0..7 {
+            pulse.step_sweep();
+            periods.push(pulse.timer_period);
+        }
+
+        assert_eq!(periods, [0x120, 0x120, 0x120, 0x144, 0x144, 0x144, 0x16C]);
+    }
+
+    #[test]
+    fn sweep_mute() {
+        // A period below 8, or a target period above $7FF (even with the sweep disabled),
+        // silences the channel
+        for &(period, muted) in &[(7, true), (8, false), (0x3FF, false), (0x400, true)] {
+            let mut pulse = pulse(false, 3, period);
+            assert_eq!(pulse.output() == 0, muted, "period ${:03X}", period);
+
+            pulse.write(1, 0x80);
+            pulse.step_sweep();
+            assert_eq!(pulse.timer_period, period, "period ${:03X}", period);
+        }
+    }
+}
@@ -0,0 +1,225 @@
+use std::f64::consts::PI;
+
+/// Number of taps of the band-limited step (in output samples)
+const TAPS: usize = 16;
+
+/// Number of phases (sub-sample positions) of the band-limited step
+const PHASES: usize = 32;
+
+/// Band-limited resampler; converts a signal sampled at the (much higher) clock rate down
+/// to the output sample rate.
+///
+/// Every change in amplitude is added to the output as a band-limited step (a windowed sinc
+/// that is positioned to the sub-sample) so that no aliasing is introduced by the large
+/// drop in rate. The buffer holds the _differences_ between samples; samples are recovered
+/// by integrating the buffer as it is read.
+pub struct Resampler {
+    /// Output sample rate (in Hz)
+    pub rate: u32,
+
+    /// Output samples per clock
+    ratio: f64,
+
+    /// Time of the next clock (in output samples from the start of the buffer)
+    time: f64,
+
+    /// Band-limited step kernel (PHASES rows of TAPS each); each row sums to 1
+    kernel: Vec<f32>,
+
+    /// Buffer of differences
+    buffer: Vec<f32>,
+
+    /// Amplitude at the last clock
+    amplitude: f32,
+
+    /// Integrator (current sample)
+    sum: f32,
+
+    /// High-pass filter (removes the DC offset of the mixer): coefficient
+    high_pass: f32,
+
+    /// High-pass filter: last input
+    high_pass_input: f32,
+
+    /// High-pass filter: last output
+    high_pass_output: f32,
+}
+
+impl Resampler {
+    pub fn new(clock_rate: f64, rate: u32) -> Resampler {
+        // Cut-off (relative to the Nyquist frequency of the output) of the low-pass filter;
+        // a bit below 1 leaves room for the transition band
+        let cutoff = 0.9;
+
+        let mut kernel = vec![0.0; PHASES * TAPS];
+        for phase in 0..PHASES {
+            let offset = phase as f64 / PHASES as f64;
+            let row = &mut kernel[phase * TAPS..(phase + 1) * TAPS];
+
+            let mut total = 0.0;
+            for (k, tap) in row.iter_mut().enumerate() {
+                // Distance (in output samples) from the center of the step
+                let x = k as f64 - (TAPS / 2) as f64 - offset;
+
+                let sinc = if x == 0.0 {
+                    1.0
+                } else {
+                    (PI * x * cutoff).sin() / (PI * x * cutoff)
+                };
+
+                // Blackman window
+                let n = (x + (TAPS / 2 + 1) as f64) / (TAPS + 1) as f64;
+                let window = 0.42 - 0.5 * (2.0 * PI * n).cos() + 0.08 * (4.0 * PI * n).cos();
+
+                *tap = (sinc * window) as f32;
+                total += *tap;
+            }
+
+            for tap in row.iter_mut() {
+                *tap /= total;
+            }
+        }
+
+        Resampler {
+            rate: rate,
+            ratio: rate as f64 / clock_rate,
+            time: 0.0,
+            kernel: kernel,
+            buffer: vec![0.0; TAPS + 1],
+            amplitude: 0.0,
+            sum: 0.0,
+            high_pass: (-2.0 * PI * 90.0 / rate as f64).exp() as f32,
+            high_pass_input: 0.0,
+            high_pass_output: 0.0,
+        }
+    }
+
+    /// Add the amplitude of the signal for the next clock.
+    pub fn add(&mut self, amplitude: f32) {
+        let delta = amplitude - self.amplitude;
+        if delta != 0.0 {
+            self.amplitude = amplitude;
+
+            let index = self.time as usize;
+            let phase = ((self.time - index as f64) * PHASES as f64) as usize;
+
+            if self.buffer.len() < index + TAPS {
+                self.buffer.resize(index + TAPS, 0.0);
+            }
+
+            let row = &self.kernel[phase * TAPS..(phase + 1) * TAPS];
+            for (sample, tap) in self.buffer[index..index + TAPS].iter_mut().zip(row) {
+                *sample += delta * tap;
+            }
+        }
+
+        self.time += self.ratio;
+    }
+
+    /// Number of samples that are complete (no further clock can change them)
+    pub fn available(&self) -> usize {
+        self.time as usize
+    }
+
+    /// Move the complete samples to `out`.
+    pub fn read(&mut self, out: &mut Vec<f32>) {
+        let count = self.available();
+        if self.buffer.len() < count + TAPS {
+            self.buffer.resize(count + TAPS, 0.0);
+        }
+
+        for delta in self.buffer.drain(..count) {
+            self.sum += delta;
+
+            let output = self.sum - self.high_pass_input + self.high_pass * self.high_pass_output;
+            self.high_pass_input = self.sum;
+            self.high_pass_output = output;
+
+            out.push(output);
+        }
+
+        self.time -= count as f64;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PHASES, Resampler, TAPS};
+
+    const CLOCK_RATE: f64 = 1789773.0;
+
+    /// Output of a second of a square wave of `frequency` (Hz) and `amplitude`
+    fn square(frequency: f64, amplitude: f32) -> Vec<f32> {
+        let mut resampler = Resampler::new(CLOCK_RATE, 44100);
+        let period = CLOCK_RATE / frequency;
+
+        let mut out = Vec::new();
+        for clock in 0..(CLOCK_RATE as usize) {
+            let high = (clock as f64 % period) < period / 2.0;
+            resampler.add(if high { amplitude } else { 0.0 });
+
+            if resampler.available() >= 512 {
+                resampler.read(&mut out);
+            }
+        }
+
+        resampler.read(&mut out);
+
+        out
+    }
+
+    #[test]
+    fn kernel() {
+        let resampler = Resampler::new(CLOCK_RATE, 44100);
+        for phase in 0..PHASES {
+            let row = &resampler.kernel[phase * TAPS..(phase + 1) * TAPS];
+            assert!((row.iter().sum::<f32>() - 1.0).abs() < 1e-5, "phase {}", phase);
+        }
+    }
+
+    #[test]
+    fn rate() {
+        for &rate in &[22050, 44100, 48000] {
+            let mut resampler = Resampler::new(CLOCK_RATE, rate);
+            let mut out = Vec::new();
+            for _ in 0..(CLOCK_RATE as usize) {
+                resampler.add(0.0);
+            }
+
+            resampler.read(&mut out);
+            assert!((out.len() as i64 - rate as i64).abs() <= 1,
+                    "{} samples at {} Hz",
+                    out.len(),
+                    rate);
+        }
+    }
+
+    #[test]
+    fn frequency() {
+        // Each period of the wave crosses 0 twice once the high-pass filter has centered it
+        // (skip the first 0.1 s)
+        let out = square(1000.0, 0.5);
+        let crossings = out[4410..]
+            .windows(2)
+            .filter(|pair| (pair[0] < 0.0) != (pair[1] < 0.0))
+            .count();
+
+        assert!((crossings as i64 - 1800).abs() <= 2, "{} crossings", crossings);
+    }
+
+    #[test]
+    fn high_pass() {
+        // A constant signal decays to 0
+        let mut resampler = Resampler::new(CLOCK_RATE, 44100);
+        let mut out = Vec::new();
+        for _ in 0..(CLOCK_RATE as usize) {
+            resampler.add(0.5);
+        }
+
+        resampler.read(&mut out);
+
+        let peak = out.iter().cloned().fold(0.0, f32::max);
+        assert!(peak > 0.45 && peak < 0.55, "peak {}", peak);
+        assert!(out[out.len() - 1].abs() < 0.001);
+    }
+}
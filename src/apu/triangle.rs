@@ -0,0 +1,183 @@
+use super::length::LengthCounter;
+use state::State;
+
+/// Triangle sequence (32 steps)
+const SEQUENCE: [u8; 32] = [15, 14, 13, 12, 11, 10, 9, 8, 7, 6, 5, 4, 3, 2, 1, 0, 0, 1, 2, 3, 4,
+                            5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15];
+
+/// Triangle channel
+#[derive(Default)]
+pub struct Triangle {
+    /// [$4008:7] Control; halts the length counter and keeps the linear counter reloading
+    control: bool,
+
+    /// [$4008:0-6] Linear counter reload value
+    linear_period: u8,
+
+    /// Linear counter
+    linear_counter: u8,
+
+    /// Set by a write to $400B; the linear counter is reloaded on the next clock
+    linear_reload: bool,
+
+    /// [$400A, $400B:0-2] Timer period (11 bits)
+    timer_period: u16,
+
+    /// Timer; counts down and steps the sequencer when it reaches zero
+    timer: u16,
+
+    /// Position in the sequence
+    sequence: u8,
+
+    pub length: LengthCounter,
+}
+
+impl Triangle {
+    /// Write to one of the 4 registers of the channel (indexed from 0).
+    pub fn write(&mut self, index: u16, value: u8) {
+        match index {
+            0 => {
+                self.control = value & 0x80 != 0;
+                self.length.halt = value & 0x80 != 0;
+                self.linear_period = value & 0x7F;
+            }
+
+            // Unused
+            1 => {}
+
+            2 => {
+                self.timer_period = (self.timer_period & 0x700) | value as u16;
+            }
+
+            3 => {
+                self.timer_period = (self.timer_period & 0xFF) | ((value as u16 & 7) << 8);
+                self.length.load(value >> 3);
+
+                self.linear_reload = true;
+            }
+
+            _ => unreachable!(),
+        }
+    }
+
+    /// Clocked every CPU cycle
+    pub fn step_timer(&mut self) {
+        if self.timer == 0 {
+            self.timer = self.timer_period;
+
+            // The sequencer only steps while both counters are non-zero
+            if self.linear_counter > 0 && self.length.active() {
+                self.sequence = (self.sequence + 1) & 31;
+            }
+        } else {
+            self.timer -= 1;
+        }
+    }
+
+    /// Clocked by the quarter-frame signal of the frame counter
+    pub fn step_linear(&mut self) {
+        if self.linear_reload {
+            self.linear_counter = self.linear_period;
+        } else if self.linear_counter > 0 {
+            self.linear_counter -= 1;
+        }
+
+        if !self.control {
+            self.linear_reload = false;
+        }
+    }
+
+    /// NOTE: When the sequencer is halted the channel keeps outputting its last value
+    pub fn output(&self) -> u8 {
+        SEQUENCE[self.sequence as usize]
+    }
+
+    pub fn sync(&mut self, s: &mut State) {
+        s.bool(&mut self.control);
+        s.u8(&mut self.linear_period);
+        s.u8(&mut self.linear_counter);
+        s.bool(&mut self.linear_reload);
+        s.u16(&mut self.timer_period);
+        s.u16(&mut self.timer);
+        s.u8(&mut self.sequence);
+
+        self.length.sync(s);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SEQUENCE, Triangle};
+
+    /// Triangle with a timer period of 0 (the sequence steps on every step of the timer),
+    /// the length counter loaded, and a linear counter period of `linear`
+    fn triangle(control: bool, linear: u8) -> Triangle {
+        let mut triangle: Triangle = Default::default();
+        triangle.length.set_enabled(true);
+
+        triangle.write(0, ((control as u8) << 7) | linear);
+        triangle.write(2, 0);
+        triangle.write(3, 0x08);
+
+        triangle
+    }
+
+    #[test]
+    fn sequence() {
+        let mut triangle = triangle(false, 0x7F);
+        triangle.step_linear();
+
+        for i in 0..64 {
+            assert_eq!(triangle.output(), SEQUENCE[i & 31], "step {}", i);
+            triangle.step_timer();
+        }
+    }
+
+    #[test]
+    fn linear_counter() {
+        let mut triangle = triangle(false, 3);
+
+        // Reloaded on the first clock after the write to $400B; then counts down to 0
+        let mut counters = Vec::new();
+        for _ in 0..5 {
+            triangle.step_linear();
+            counters.push(triangle.linear_counter);
+        }
+
+        assert_eq!(counters, [3, 2, 1, 0, 0]);
+
+        // The sequence is halted (and the output is held) while the counter is 0
+        triangle.step_timer();
+        assert_eq!(triangle.output(), SEQUENCE[0]);
+    }
+
+    #[test]
+    fn control() {
+        // With control set, the counter is reloaded on every clock
+        let mut triangle = triangle(true, 3);
+        for _ in 0..5 {
+            triangle.step_linear();
+            assert_eq!(triangle.linear_counter, 3);
+        }
+
+        // Clearing control stops the reload after the next clock
+        triangle.write(0, 3);
+        triangle.step_linear();
+        triangle.step_linear();
+        assert_eq!(triangle.linear_counter, 2);
+    }
+
+    #[test]
+    fn length_counter() {
+        // The sequence is also halted when the length counter is 0
+        let mut triangle = triangle(false, 0x7F);
+        triangle.step_linear();
+        triangle.length.set_enabled(false);
+
+        for _ in 0..4 {
+            triangle.step_timer();
+        }
+
+        assert_eq!(triangle.output(), SEQUENCE[0]);
+    }
+}
@@ -0,0 +1,468 @@
+use std::collections::HashMap;
+use std::env;
+use std::fs::File;
+use std::io::Read;
+
+use sdl2::controller::{Axis, Button as ControllerButton};
+use sdl2::keyboard::Scancode;
+use toml;
+
+use lintel_nes::{Button, Adapter, PLAYERS, POWER_PAD_BUTTONS};
+
+/// Device to attach to a controller port (or the expansion port)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PortDevice {
+    /// Standard controller (or the 4-player adapter); nothing in the expansion port
+    Controller,
+
+    /// Zapper (aimed and fired with the mouse)
+    Zapper,
+
+    /// Arkanoid controller (moved and fired with the mouse)
+    Vaus,
+
+    /// Power Pad (Family Trainer in the expansion port; pressed with the keyboard)
+    PowerPad,
+
+    /// SNES mouse (moved with the mouse)
+    SnesMouse,
+}
+
+impl Default for PortDevice {
+    fn default() -> Self {
+        PortDevice::Controller
+    }
+}
+
+/// Position past which an axis counts as pressed (out of 32767)
+const AXIS_THRESHOLD: i16 = 16384;
+
+/// Default bindings (also an example of the format of the bindings file).
+///
+/// `adapter` selects the 4-player adapter (`none`, `four-score`, or `hori`); `port1` and
+/// `port2` select the device in each controller port (`controller`, `zapper`, `vaus`,
+/// `power-pad`, or `snes-mouse`) and `expansion` the device in the expansion port of the
+/// Famicom (`none`, `vaus`, or `family-trainer`). Each player has a table of keyboard
+/// bindings (SDL scancode names) and a table of game controller bindings (SDL game
+/// controller button names or axis names prefixed with the direction, `+` or `-`). The
+/// Power Pad (and Family Trainer) has a table of keyboard bindings for its buttons (1-12).
+/// `fast-forward` and `slow-motion` are the speeds (relative to normal) while the
+/// fast-forward and slow motion keys are held.
+/// A binding is either one name or an array of names. The bindings file is applied over
+/// this key by key within the top-level tables: a key in `[power-pad]` replaces the binding
+/// of that button only, and a table like `[player1.keyboard]` replaces that table of the
+/// player whole (the other tables of the player are kept).
+const DEFAULT: &'static str = r#"
+adapter = "none"
+port1 = "controller"
+port2 = "controller"
+expansion = "none"
+fast-forward = 4.0
+slow-motion = 0.5
+
+[power-pad]
+1 = "1"
+2 = "2"
+3 = "3"
+4 = "4"
+5 = "Q"
+6 = "W"
+7 = "E"
+8 = "R"
+9 = "A"
+10 = "S"
+11 = "D"
+12 = "F"
+
+[player1.keyboard]
+a = "Z"
+b = "X"
+select = "Left Shift"
+start = "Return"
+up = "Up"
+down = "Down"
+left = "Left"
+right = "Right"
+
+[player1.controller]
+a = "a"
+b = "x"
+select = "back"
+start = "start"
+up = ["dpup", "-lefty"]
+down = ["dpdown", "+lefty"]
+left = ["dpleft", "-leftx"]
+right = ["dpright", "+leftx"]
+
+[player2.keyboard]
+a = "M"
+b = "N"
+select = "Right Shift"
+start = "Backspace"
+up = "I"
+down = "K"
+left = "J"
+right = "L"
+
+[player2.controller]
+a = "a"
+b = "x"
+select = "back"
+start = "start"
+up = ["dpup", "-lefty"]
+down = ["dpdown", "+lefty"]
+left = ["dpleft", "-leftx"]
+right = ["dpright", "+leftx"]
+
+[player3.keyboard]
+
+[player3.controller]
+a = "a"
+b = "x"
+select = "back"
+start = "start"
+up = ["dpup", "-lefty"]
+down = ["dpdown", "+lefty"]
+left = ["dpleft", "-leftx"]
+right = ["dpright", "+leftx"]
+
+[player4.keyboard]
+
+[player4.controller]
+a = "a"
+b = "x"
+select = "back"
+start = "start"
+up = ["dpup", "-lefty"]
+down = ["dpdown", "+lefty"]
+left = ["dpleft", "-leftx"]
+right = ["dpright", "+leftx"]
+"#;
+
+/// Input on a game controller
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ControllerInput {
+    Button(ControllerButton),
+
+    /// Axis pushed past the threshold in the positive (true) or negative (false) direction
+    Axis(Axis, bool),
+}
+
+/// Bindings of host input (keyboard and game controllers) to the buttons of each player
+#[derive(Default)]
+pub struct Bindings {
+    /// 4-player adapter
+    pub adapter: Adapter,
+
+    /// Device in each controller port
+    pub ports: [PortDevice; 2],
+
+    /// Device in the expansion port
+    pub expansion: PortDevice,
+
+    /// Speed of fast-forward (relative to normal)
+    pub fast_forward: f64,
+
+    /// Speed of slow motion (relative to normal)
+    pub slow_motion: f64,
+
+    /// Keyboard: scancode to buttons of the Power Pad (0-11)
+    power_pad: HashMap<Scancode, Vec<usize>>,
+
+    /// Keyboard: scancode to buttons (of any player)
+    keys: HashMap<Scancode, Vec<(usize, Button)>>,
+
+    /// Game controller: input to buttons (for the game controller of each player)
+    controllers: [Vec<(ControllerInput, Button)>; PLAYERS],
+}
+
+impl Bindings {
+    /// Load the bindings from the file named by `LINTEL_BINDINGS` (or `bindings.toml` in
+    /// the working directory) over the defaults (see `DEFAULT`); the defaults are used
+    /// as they are if the file does not exist or can not be read.
+    pub fn load() -> Bindings {
+        let filename = env::var("LINTEL_BINDINGS").unwrap_or("bindings.toml".to_string());
+
+        let mut text = String::new();
+        if let Ok(mut file) = File::open(&filename) {
+            if let Err(error) = file.read_to_string(&mut text) {
+                warn!("bindings: failed to read \"{}\" ({}); using the defaults",
+                      filename,
+                      error);
+
+                text.clear();
+            }
+        }
+
+        Bindings::from_str(&text)
+    }
+
+    /// Build the bindings from the text of a bindings file (applied over the defaults).
+    pub fn from_str(text: &str) -> Bindings {
+        let mut table = parse(DEFAULT);
+
+        for (key, value) in parse(text) {
+            match (table.get_mut(&key), value) {
+                (Some(&mut toml::Value::String(ref mut name)), toml::Value::String(ref value)) => {
+                    *name = value.clone();
+                }
+
+                (Some(&mut toml::Value::Float(ref mut speed)), toml::Value::Float(value)) => {
+                    *speed = value;
+                }
+
+                (Some(&mut toml::Value::Float(ref mut speed)), toml::Value::Integer(value)) => {
+                    *speed = value as f64;
+                }
+
+                (Some(&mut toml::Value::Table(ref mut player)),
+                 toml::Value::Table(ref overrides)) => {
+                    for (key, value) in overrides {
+                        player.insert(key.clone(), value.clone());
+                    }
+                }
+
+                _ => {
+                    warn!("bindings: unknown or mistyped key \"{}\"", key);
+                }
+            }
+        }
+
+        let table = toml::Value::Table(table);
+        let mut bindings: Bindings = Default::default();
+
+        bindings.adapter = match table.lookup("adapter").and_then(|value| value.as_str()) {
+            Some("four-score") => Adapter::FourScore,
+            Some("hori") => Adapter::Hori,
+            Some("none") => Adapter::None,
+
+            name => {
+                warn!("bindings: unknown adapter {:?}", name);
+
+                Adapter::None
+            }
+        };
+
+        for port in 0..2 {
+            let key = format!("port{}", port + 1);
+            bindings.ports[port] = match table.lookup(&key).and_then(|value| value.as_str()) {
+                Some("controller") => PortDevice::Controller,
+                Some("zapper") => PortDevice::Zapper,
+                Some("vaus") => PortDevice::Vaus,
+                Some("power-pad") => PortDevice::PowerPad,
+                Some("snes-mouse") => PortDevice::SnesMouse,
+
+                name => {
+                    warn!("bindings: unknown device {:?} for {}", name, key);
+
+                    PortDevice::Controller
+                }
+            };
+        }
+
+        bindings.expansion = match table.lookup("expansion").and_then(|value| value.as_str()) {
+            Some("none") => PortDevice::Controller,
+            Some("vaus") => PortDevice::Vaus,
+            Some("family-trainer") => PortDevice::PowerPad,
+
+            name => {
+                warn!("bindings: unknown device {:?} for expansion", name);
+
+                PortDevice::Controller
+            }
+        };
+
+        bindings.fast_forward = speed(&table, "fast-forward");
+        bindings.slow_motion = speed(&table, "slow-motion");
+
+        if let Some(power_pad) = table.lookup("power-pad").and_then(|value| value.as_table()) {
+            for (key, value) in power_pad {
+                let button = match key.parse::<usize>() {
+                    Ok(button) if button >= 1 && button <= POWER_PAD_BUTTONS => button - 1,
+
+                    _ => {
+                        warn!("bindings: unknown Power Pad button \"{}\"", key);
+
+                        continue;
+                    }
+                };
+
+                match value.as_str().and_then(Scancode::from_name) {
+                    Some(scancode) => {
+                        bindings.power_pad.entry(scancode).or_insert(Vec::new()).push(button);
+                    }
+
+                    None => {
+                        warn!("bindings: unknown key {} for Power Pad button {}", value, key);
+                    }
+                }
+            }
+        }
+
+        for player in 0..PLAYERS {
+            let section = format!("player{}", player + 1);
+
+            if let Some(keyboard) = table.lookup(&format!("{}.keyboard", section)) {
+                for (button, name) in names(keyboard) {
+                    match Scancode::from_name(name) {
+                        Some(scancode) => {
+                            let buttons = bindings.keys.entry(scancode).or_insert(Vec::new());
+                            buttons.push((player, button));
+                        }
+
+                        None => {
+                            warn!("bindings: unknown key \"{}\"", name);
+                        }
+                    }
+                }
+            }
+
+            if let Some(controller) = table.lookup(&format!("{}.controller", section)) {
+                for (button, name) in names(controller) {
+                    match parse_controller_input(name) {
+                        Some(input) => {
+                            bindings.controllers[player].push((input, button));
+                        }
+
+                        None => {
+                            warn!("bindings: unknown game controller input \"{}\"", name);
+                        }
+                    }
+                }
+            }
+        }
+
+        bindings
+    }
+
+    /// Buttons (of any player) bound to a key
+    pub fn key(&self, scancode: Scancode) -> &[(usize, Button)] {
+        self.keys.get(&scancode).map(|buttons| &buttons[..]).unwrap_or(&[])
+    }
+
+    /// Buttons of the Power Pad (0-11) bound to a key
+    pub fn power_pad_key(&self, scancode: Scancode) -> &[usize] {
+        self.power_pad.get(&scancode).map(|buttons| &buttons[..]).unwrap_or(&[])
+    }
+
+    /// Buttons bound to a game controller button (for the game controller of `player`)
+    pub fn controller_button<'a>(&'a self,
+                                 player: usize,
+                                 button: ControllerButton)
+                                 -> Box<Iterator<Item = Button> + 'a> {
+        Box::new(self.controllers[player]
+            .iter()
+            .filter(move |&&(input, _)| input == ControllerInput::Button(button))
+            .map(|&(_, button)| button))
+    }
+
+    /// Buttons bound to a game controller axis with their state for the position of the
+    /// axis (for the game controller of `player`)
+    pub fn controller_axis<'a>(&'a self,
+                               player: usize,
+                               axis: Axis,
+                               value: i16)
+                               -> Box<Iterator<Item = (Button, bool)> + 'a> {
+        Box::new(self.controllers[player].iter().filter_map(move |&(input, button)| {
+            match input {
+                ControllerInput::Axis(a, true) if a == axis => {
+                    Some((button, value > AXIS_THRESHOLD))
+                }
+
+                ControllerInput::Axis(a, false) if a == axis => {
+                    Some((button, value < -AXIS_THRESHOLD))
+                }
+
+                _ => None,
+            }
+        }))
+    }
+
+    /// All buttons bound to the game controller of `player`
+    pub fn controller_buttons<'a>(&'a self, player: usize) -> Box<Iterator<Item = Button> + 'a> {
+        Box::new(self.controllers[player].iter().map(|&(_, button)| button))
+    }
+}
+
+fn parse(text: &str) -> toml::Table {
+    let mut parser = toml::Parser::new(text);
+    match parser.parse() {
+        Some(table) => table,
+
+        None => {
+            for error in &parser.errors {
+                let (line, column) = parser.to_linecol(error.lo);
+                warn!("bindings: {} (at {}:{})", error.desc, line + 1, column + 1);
+            }
+
+            Default::default()
+        }
+    }
+}
+
+/// Speed (relative to normal; must be positive)
+fn speed(table: &toml::Value, key: &str) -> f64 {
+    match table.lookup(key).and_then(|value| value.as_float()) {
+        Some(speed) if speed > 0.0 => speed,
+
+        speed => {
+            warn!("bindings: invalid speed {:?} for {}", speed, key);
+
+            1.0
+        }
+    }
+}
+
+/// Names bound to each button in a table of bindings
+fn names(table: &toml::Value) -> Vec<(Button, &str)> {
+    let mut result = Vec::new();
+
+    if let Some(table) = table.as_table() {
+        for (key, value) in table {
+            let button = match &key[..] {
+                "a" => Button::A,
+                "b" => Button::B,
+                "select" => Button::Select,
+                "start" => Button::Start,
+                "up" => Button::Up,
+                "down" => Button::Down,
+                "left" => Button::Left,
+                "right" => Button::Right,
+
+                _ => {
+                    warn!("bindings: unknown button \"{}\"", key);
+
+                    continue;
+                }
+            };
+
+            match *value {
+                toml::Value::String(ref name) => {
+                    result.push((button, &name[..]));
+                }
+
+                toml::Value::Array(ref names) => {
+                    for name in names.iter().filter_map(|name| name.as_str()) {
+                        result.push((button, name));
+                    }
+                }
+
+                _ => {
+                    warn!("bindings: expected a name or an array of names for \"{}\"", key);
+                }
+            }
+        }
+    }
+
+    result
+}
+
+fn parse_controller_input(name: &str) -> Option<ControllerInput> {
+    if name.starts_with('+') {
+        Axis::from_string(&name[1..]).map(|axis| ControllerInput::Axis(axis, true))
+    } else if name.starts_with('-') {
+        Axis::from_string(&name[1..]).map(|axis| ControllerInput::Axis(axis, false))
+    } else {
+        ControllerButton::from_string(name).map(ControllerInput::Button)
+    }
+}
@@ -4,6 +4,7 @@ use ppu;
 use apu;
 use input;
 use cartridge;
+use state::State;
 
 #[derive(Default)]
 pub struct Bus {
@@ -11,7 +12,7 @@ pub struct Bus {
     pub ppu: ppu::PPU,
 
     /// Component: APU
-    apu: apu::APU,
+    pub apu: apu::APU,
 
     /// Component: Memory Controller
     mmu: mmu::MMU,
@@ -21,6 +22,9 @@ pub struct Bus {
 
     /// NMI occurred (signal); set by the PPU and read by the CPU
     pub nmi_occurred: bool,
+
+    /// Cycles the CPU was halted for by DMC DMA (that the CPU has yet to count)
+    dma_cycles: u32,
 }
 
 impl Bus {
@@ -30,6 +34,7 @@ impl Bus {
 
     pub fn reset(&mut self) {
         self.nmi_occurred = false;
+        self.dma_cycles = 0;
 
         self.ppu.reset();
         self.apu.reset();
@@ -37,7 +42,57 @@ impl Bus {
         self.input.reset();
     }
 
+    /// [CPU] Internal RAM
+    pub fn ram(&self) -> &[u8] {
+        self.mmu.cpu_ram()
+    }
+
+    /// Hash of the ROM in the cartridge
+    pub fn rom_hash(&self) -> u32 {
+        self.mmu.rom_hash()
+    }
+
+    pub fn sync(&mut self, s: &mut State) {
+        s.bool(&mut self.nmi_occurred);
+
+        self.ppu.sync(s);
+        self.apu.sync(s);
+        self.mmu.sync(s);
+        self.input.sync(s);
+    }
+
+    /// Reset (soft; the reset button); memory and the cartridge are unchanged
+    pub fn soft_reset(&mut self) {
+        self.nmi_occurred = false;
+        self.dma_cycles = 0;
+
+        self.ppu.soft_reset();
+        self.apu.soft_reset();
+    }
+
+    /// Read from the cartridge ($4020-$FFFF) without side effects on the rest of the bus
+    pub fn peek(&mut self, address: u16) -> u8 {
+        let mut value = 0;
+        cpu::Controller::try_read(&mut self.mmu, address, &mut value);
+
+        value
+    }
+
+    /// Take the number of cycles the CPU was halted for by DMC DMA since the last call
+    pub fn take_dma_cycles(&mut self) -> u32 {
+        let cycles = self.dma_cycles;
+        self.dma_cycles = 0;
+
+        cycles
+    }
+
+    /// IRQ (signal); level-triggered and asserted by any of the components on the bus
+    pub fn irq(&self) -> bool {
+        self.apu.irq() || self.mmu.irq()
+    }
+
     pub fn step(&mut self) {
+        self.mmu.step();
         self.apu.step();
 
         // 3 PPU Steps ("dots") to 1 CPU Step ("cycle")
@@ -46,7 +101,58 @@ impl Bus {
         self.ppu.step(&mut self.mmu, &mut self.nmi_occurred);
     }
 
+    /// Read (by the CPU); a pending DMC DMA halts the CPU on this read and steals
+    /// the cycles it needs before the read completes
     pub fn read(&mut self, address: u16) -> u8 {
+        if let Some(sample_address) = self.apu.dma_request() {
+            self.dmc_dma(address, sample_address);
+        }
+
+        self.read_direct(address)
+    }
+
+    /// DMC DMA; the CPU is halted on a read of `address`
+    fn dmc_dma(&mut self, address: u16, sample_address: u16) {
+        // Halt cycle, dummy cycle, and (if needed) an alignment cycle; the halted
+        // CPU keeps repeating its read of `address` during each of these
+        let mut cycles = 0;
+        loop {
+            match address {
+                // NOTE: The input ports see repeated reads on consecutive cycles as one
+                //       read (/OE stays asserted); the get cycle reads from elsewhere,
+                //       so the read that the CPU resumes with clocks the port again
+                //       and a bit is lost
+                0x4016 | 0x4017 if cycles == 0 => {
+                    self.input.read(address, &self.ppu);
+                }
+
+                // The PPU sees every read (reads of $2007 increment the VRAM address
+                // and reads of $2002 clear VBlank)
+                0x2000...0x3FFF => {
+                    self.ppu.read(&mut self.mmu, address);
+                }
+
+                _ => {}
+            }
+
+            self.step();
+            cycles += 1;
+
+            if cycles >= 2 && self.apu.get_cycle() {
+                break;
+            }
+        }
+
+        // Get cycle: fetch the sample byte
+        let value = self.read_direct(sample_address);
+        self.apu.dma_fill(value);
+        self.step();
+
+        // The CPU counts these (see `cpu::Context::halted`)
+        self.dma_cycles += cycles + 1;
+    }
+
+    fn read_direct(&mut self, address: u16) -> u8 {
         let mut value: u8 = 0xFF;
         if cpu::Controller::try_read(&mut self.mmu, address, &mut value) {
             return value;
@@ -60,7 +166,7 @@ impl Bus {
             0x4000...0x4013 | 0x4015 => self.apu.read(address),
 
             // Input
-            0x4016 | 0x4017 => self.input.read(address),
+            0x4016 | 0x4017 => self.input.read(address, &self.ppu),
 
             _ => {
                 warn!("unhandled read at ${:04X}", address);
@@ -71,22 +177,6 @@ impl Bus {
     }
 
     pub fn write(&mut self, address: u16, value: u8) {
-        if address == 0x6000 {
-            warn!("write: $6000 <- ${:02X}", value);
-
-            let mut i = 0;
-            loop {
-                let c = self.read(0x6004 + i);
-                if c == 0x00 {
-                    break;
-                }
-
-                print!("{}", c as char);
-
-                i += 1;
-            }
-        }
-
         if cpu::Controller::try_write(&mut self.mmu, address, value) {
             return;
         }
@@ -103,7 +193,7 @@ impl Bus {
                 let mut src = (value as u16) << 8;
                 let src_end = src + 0xFF;
                 while src < src_end {
-                    let r = self.read(src);
+                    let r = self.read_direct(src);
                     self.write(0x2004, r);
 
                     src += 1;
@@ -111,13 +201,13 @@ impl Bus {
             }
 
             // APU Registers
-            0x4000...0x4013 | 0x4015 => {
+            0x4000...0x4013 | 0x4015 | 0x4017 => {
                 self.apu.write(address, value);
             }
 
             // Input
-            0x4016 | 0x4017 => {
-                self.input.write(address, value);
+            0x4016 => {
+                self.input.write(address, value, &self.ppu);
             }
 
             _ => {
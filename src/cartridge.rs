@@ -1,7 +1,10 @@
 use std::fs::File;
-use std::io::Read;
+use std::io::{self, Read};
 use std::vec::Vec;
 
+use controller;
+use state;
+
 #[derive(Default)]
 pub struct Cartridge {
     /// Program ROM (PRG-ROM)
@@ -22,50 +25,158 @@ pub struct Cartridge {
     ///     1 = Vertical
     ///     2 = 4-Screen
     pub vram_mirroring: u8,
+
+    /// Hash of the ROM (PRG-ROM and CHR-ROM); identifies the ROM of a save state
+    pub hash: u32,
 }
 
 impl Cartridge {
-    pub fn open(&mut self, filename: &str) {
-        // TODO: Error handling
-        let mut stream = File::open(filename).unwrap();
+    /// Load an iNES ROM image from a file.
+    pub fn open(&mut self, filename: &str) -> io::Result<()> {
+        let mut stream = File::open(filename)?;
+
+        self.read(&mut stream)
+    }
 
+    /// Load an iNES ROM image from a stream.
+    ///
+    /// Every supported mapper works with any size of PRG-ROM and CHR the header can give
+    /// (PRG-ROM in units of 16 KiB and CHR-ROM in units of 8 KiB; banks past the end wrap
+    /// around and a bank larger than the image mirrors it) so only the header is checked.
+    pub fn read<R: Read>(&mut self, stream: &mut R) -> io::Result<()> {
         // Read in file header
         let mut header = vec![0u8; 0x10];
-        stream.read_exact(&mut header).unwrap();
+        read_exact(stream, &mut header)?;
 
         // Validate file ID
         if !(header[0] == 0x4E && header[1] == 0x45 && header[2] == 0x53 && header[3] == 0x1A) {
             // Bad file header; not iNES
             // TODO: Support other ROM formats?
-            panic!("unknown or unsupported ROM-Image format");
+            return Err(invalid("unknown or unsupported ROM-Image format"));
+        }
+
+        // Build iNes 1.0 mapper number
+        // TODO: Flesh this out more
+        self.ines_mapper = ((header[6] >> 4) | (header[7] & 0xF0)) as u16;
+
+        if !controller::SUPPORTED.contains(&self.ines_mapper) {
+            return Err(invalid(&format!("unknown/unsupported iNES Mapper: {}", self.ines_mapper)));
         }
 
         // Set mirroring mode of VRAM
         self.vram_mirroring = header[6] & 0x1;
 
-        // Read in PRG-ROM
-        self.prg_rom.clear();
-        if header[4] > 0 {
-            self.prg_rom.resize((header[4] as usize) * 16 * 1024, 0);
-            stream.read_exact(&mut self.prg_rom).unwrap();
+        // Read in PRG-ROM; every board has some (the vectors of the CPU are in it)
+        if header[4] == 0 {
+            return Err(invalid("no PRG-ROM in ROM-Image"));
         }
 
+        // Skip the trainer (512 bytes; loaded at $7000 by some copiers; not supported)
+        if header[6] & 0x4 != 0 {
+            let mut trainer = vec![0u8; 0x200];
+            read_exact(stream, &mut trainer)?;
+        }
+
+        self.prg_rom.clear();
+        self.prg_rom.resize((header[4] as usize) * 16 * 1024, 0);
+        read_exact(stream, &mut self.prg_rom)?;
+
         // Allocate PRG-RAM (A size of $0 indicates $1 because this header format is _old_)
         let prg_ram_size = (if header[8] == 0 { 8 } else { header[8] }) as usize * 8 * 1024;
+        self.prg_ram.clear();
         self.prg_ram.resize(prg_ram_size, 0);
 
-        // Read in CHR-ROM
+        // Read in CHR-ROM (or allocate CHR-RAM)
         self.chr.clear();
-        if header[5] > 0 {
+        self.chr_mutable = header[5] == 0;
+        if !self.chr_mutable {
             self.chr.resize((header[5] as usize) * 8 * 1024, 0);
-            stream.read_exact(&mut self.chr).unwrap();
+            read_exact(stream, &mut self.chr)?;
         } else {
             self.chr.resize(1 * 8 * 1024, 0);
-            self.chr_mutable = true;
         }
 
-        // Build iNes 1.0 mapper number
-        // TODO: Flesh this out more
-        self.ines_mapper = ((header[6] >> 4) | (header[7] & 0xF0)) as u16;
+        self.hash = if self.chr_mutable {
+            state::hash(&[&self.prg_rom])
+        } else {
+            state::hash(&[&self.prg_rom, &self.chr])
+        };
+
+        Ok(())
+    }
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// `Read::read_exact`; the ROM-Image is truncated if the stream ends early
+fn read_exact<R: Read>(stream: &mut R, buffer: &mut [u8]) -> io::Result<()> {
+    stream.read_exact(buffer).map_err(|error| if error.kind() == io::ErrorKind::UnexpectedEof {
+        invalid("truncated ROM-Image")
+    } else {
+        error
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::Cartridge;
+
+    fn image(chr_banks: u8, trainer: bool) -> Vec<u8> {
+        let flags = if trainer { 0x4 } else { 0 };
+        let mut image = vec![0x4E, 0x45, 0x53, 0x1A, 1, chr_banks, flags, 0];
+        image.extend(vec![0; 8]);
+
+        if trainer {
+            image.extend(vec![0xEE; 0x200]);
+        }
+
+        image.extend(vec![0x11; 0x4000]);
+        image.extend(vec![0x22; chr_banks as usize * 0x2000]);
+
+        image
+    }
+
+    fn load(cartridge: &mut Cartridge, image: Vec<u8>) {
+        cartridge.read(&mut Cursor::new(image)).unwrap();
+    }
+
+    #[test]
+    fn reload() {
+        let mut fresh: Cartridge = Default::default();
+        load(&mut fresh, image(1, false));
+
+        // CHR-RAM, then CHR-ROM into the same cartridge
+        let mut reused: Cartridge = Default::default();
+        load(&mut reused, image(0, false));
+        assert!(reused.chr_mutable);
+
+        reused.prg_ram[0] = 0x42;
+        load(&mut reused, image(1, false));
+
+        assert!(!reused.chr_mutable);
+        assert_eq!(reused.hash, fresh.hash);
+        assert_eq!(reused.prg_ram[0], 0);
+    }
+
+    #[test]
+    fn trainer() {
+        let mut cartridge: Cartridge = Default::default();
+        load(&mut cartridge, image(1, true));
+
+        assert!(cartridge.prg_rom.iter().all(|&value| value == 0x11));
+        assert!(cartridge.chr.iter().all(|&value| value == 0x22));
+    }
+
+    #[test]
+    fn truncated() {
+        let mut image = image(1, false);
+        image.truncate(0x3000);
+
+        let mut cartridge: Cartridge = Default::default();
+        assert!(cartridge.read(&mut Cursor::new(image)).is_err());
     }
 }
@@ -0,0 +1,43 @@
+use cartridge::Cartridge;
+use super::{Discrete, Mirroring, bank_index};
+
+/// AxROM (iNES Mapper 7)
+///
+/// Switches 32 KiB of PRG-ROM at a time and selects the 1 KiB page of V-RAM used for
+/// single-screen mirroring. CHR is 8 KiB of RAM.
+///
+/// NOTE: Only AMROM and AOROM boards have bus conflicts. An iNES header cannot tell the
+///       boards apart and several games rely on ANROM _not_ having them, so bus conflicts
+///       are not emulated.
+#[derive(Default)]
+pub struct AxROM {
+    /// [$8000-$FFFF] Bank select
+    ///
+    /// 76543210
+    ///    | +++- PRG-ROM bank (32 KiB at $8000)
+    ///    +----- V-RAM page for single-screen mirroring
+    ///
+    bank: u8,
+}
+
+impl Discrete for AxROM {
+    fn bank(&mut self) -> &mut u8 {
+        &mut self.bank
+    }
+
+    fn bus_conflicts(&self) -> bool {
+        false
+    }
+
+    fn prg_rom_index(&self, cartridge: &Cartridge, address: u16) -> usize {
+        bank_index(&cartridge.prg_rom, 0x8000, (self.bank & 7) as usize, address)
+    }
+
+    fn mirroring(&self, _: &Cartridge) -> Mirroring {
+        if self.bank & 0x10 == 0 {
+            Mirroring::SingleScreenLo
+        } else {
+            Mirroring::SingleScreenHi
+        }
+    }
+}
@@ -0,0 +1,27 @@
+use cartridge::Cartridge;
+use super::{Discrete, bank_index};
+
+/// CNROM (iNES Mapper 3)
+///
+/// Fixed 16 KiB or 32 KiB of PRG-ROM (as in NROM) and a switchable 8 KiB CHR-ROM bank.
+/// Has bus conflicts.
+#[derive(Default)]
+pub struct CNROM {
+    /// [$8000-$FFFF] CHR bank (8 KiB at $0000)
+    chr_bank: u8,
+}
+
+impl Discrete for CNROM {
+    fn bank(&mut self) -> &mut u8 {
+        &mut self.chr_bank
+    }
+
+    fn prg_rom_index(&self, cartridge: &Cartridge, address: u16) -> usize {
+        // 16 KiB PRG-ROM is mirrored at $C000
+        (address as usize - 0x8000) % cartridge.prg_rom.len()
+    }
+
+    fn chr_index(&self, cartridge: &Cartridge, address: u16) -> usize {
+        bank_index(&cartridge.chr, 0x2000, self.chr_bank as usize, address)
+    }
+}
@@ -0,0 +1,30 @@
+use cartridge::Cartridge;
+use super::{Discrete, bank_index};
+
+/// GxROM (iNES Mapper 66)
+///
+/// Switches 32 KiB of PRG-ROM and 8 KiB of CHR-ROM at a time. Has bus conflicts.
+#[derive(Default)]
+pub struct GxROM {
+    /// [$8000-$FFFF] Bank select
+    ///
+    /// 76543210
+    ///   ||  ++- CHR bank (8 KiB at $0000)
+    ///   ++----- PRG-ROM bank (32 KiB at $8000)
+    ///
+    bank: u8,
+}
+
+impl Discrete for GxROM {
+    fn bank(&mut self) -> &mut u8 {
+        &mut self.bank
+    }
+
+    fn prg_rom_index(&self, cartridge: &Cartridge, address: u16) -> usize {
+        bank_index(&cartridge.prg_rom, 0x8000, ((self.bank >> 4) & 3) as usize, address)
+    }
+
+    fn chr_index(&self, cartridge: &Cartridge, address: u16) -> usize {
+        bank_index(&cartridge.chr, 0x2000, (self.bank & 3) as usize, address)
+    }
+}
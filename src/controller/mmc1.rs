@@ -0,0 +1,277 @@
+use cartridge::Cartridge;
+use state::State;
+use super::{Controller, Mirroring, palette_index};
+
+/// MMC1 (iNES Mapper 1)
+///
+/// Registers are written serially (1 bit at a time) through a 5-bit shift register
+/// mapped at $8000-$FFFF. The 5th write copies the shift register into the internal
+/// register selected by bits 13-14 of the address of that write.
+#[derive(Default)]
+pub struct MMC1 {
+    /// Shift register (filled from bit 4 towards bit 0)
+    shift: u8,
+
+    /// Number of bits written to the shift register
+    shift_count: u8,
+
+    /// [$8000-$9FFF] Control
+    ///
+    /// 43210
+    /// |||++- mirroring (0: one-screen, lo; 1: one-screen, hi; 2: vertical; 3: horizontal)
+    /// |++--- PRG-ROM bank mode (0, 1: switch 32 KiB at $8000; 2: fix first bank at $8000
+    /// |                         and switch 16 KiB at $C000; 3: fix last bank at $C000 and
+    /// |                         switch 16 KiB at $8000)
+    /// +----- CHR bank mode (0: switch 8 KiB at a time; 1: switch two separate 4 KiB banks)
+    ///
+    control: u8,
+
+    /// [$A000-$BFFF] CHR bank 0 (4 KiB at $0000 or 8 KiB at $0000 ignoring bit 0)
+    chr_bank_0: u8,
+
+    /// [$C000-$DFFF] CHR bank 1 (4 KiB at $1000; ignored in 8 KiB mode)
+    chr_bank_1: u8,
+
+    /// [$E000-$FFFF] PRG bank (bits 0-3) and PRG-RAM disable (bit 4)
+    prg_bank: u8,
+}
+
+impl MMC1 {
+    fn mirroring(&self) -> Mirroring {
+        match self.control & 3 {
+            0 => Mirroring::SingleScreenLo,
+            1 => Mirroring::SingleScreenHi,
+            2 => Mirroring::Vertical,
+            _ => Mirroring::Horizontal,
+        }
+    }
+
+    fn prg_ram_enable(&self) -> bool {
+        self.prg_bank & 0x10 == 0
+    }
+
+    fn prg_rom_index(&self, cartridge: &Cartridge, address: u16) -> usize {
+        // 512 KiB boards (SUROM) select the 256 KiB half of PRG-ROM with bit 4 of the
+        // CHR bank 0 register
+        let outer = (self.chr_bank_0 & 0x10) as usize;
+        let bank = match (self.control >> 2) & 3 {
+            // Switch 32 KiB at $8000 (ignoring bit 0 of the bank number)
+            0 | 1 => ((self.prg_bank & 0x0E) as usize) | ((address as usize >> 14) & 1),
+
+            // Fix first bank at $8000; switch 16 KiB bank at $C000
+            2 => {
+                if address < 0xC000 {
+                    0
+                } else {
+                    (self.prg_bank & 0x0F) as usize
+                }
+            }
+
+            // Fix last bank at $C000; switch 16 KiB bank at $8000
+            _ => {
+                if address < 0xC000 {
+                    (self.prg_bank & 0x0F) as usize
+                } else {
+                    0x0F
+                }
+            }
+        };
+
+        let banks = cartridge.prg_rom.len() / 0x4000;
+
+        ((outer | bank) % banks) * 0x4000 + (address as usize & 0x3FFF)
+    }
+
+    fn chr_index(&self, cartridge: &Cartridge, address: u16) -> usize {
+        // NOTE: Banks are in units of 4 KiB
+        let bank = if self.control & 0x10 == 0 {
+            // Switch 8 KiB at a time (ignoring bit 0 of the bank number)
+            ((self.chr_bank_0 & 0x1E) as usize) | ((address as usize >> 12) & 1)
+        } else if address < 0x1000 {
+            self.chr_bank_0 as usize
+        } else {
+            self.chr_bank_1 as usize
+        };
+
+        let banks = cartridge.chr.len() / 0x1000;
+
+        (bank % banks) * 0x1000 + (address as usize & 0xFFF)
+    }
+
+    fn write_register(&mut self, address: u16, value: u8) {
+        match address {
+            0x8000...0x9FFF => {
+                self.control = value;
+            }
+
+            0xA000...0xBFFF => {
+                self.chr_bank_0 = value;
+            }
+
+            0xC000...0xDFFF => {
+                self.chr_bank_1 = value;
+            }
+
+            0xE000...0xFFFF => {
+                self.prg_bank = value;
+            }
+
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl Controller for MMC1 {
+    fn reset(&mut self) {
+        self.shift = 0;
+        self.shift_count = 0;
+
+        // Power-up state is PRG-ROM bank mode 3 (fix last bank at $C000)
+        self.control = 0x0C;
+        self.chr_bank_0 = 0;
+        self.chr_bank_1 = 0;
+        self.prg_bank = 0;
+    }
+
+    fn sync(&mut self, s: &mut State) {
+        s.u8(&mut self.shift);
+        s.u8(&mut self.shift_count);
+        s.u8(&mut self.control);
+        s.u8(&mut self.chr_bank_0);
+        s.u8(&mut self.chr_bank_1);
+        s.u8(&mut self.prg_bank);
+    }
+
+    fn cpu_read(&mut self,
+                ram: &mut [u8],
+                cartridge: &mut Cartridge,
+                address: u16,
+                ptr: &mut u8)
+                -> bool {
+        *ptr = match address {
+            // Internal RAM (2KiB; mirrored 3 times)
+            0x0000...0x1FFF => ram[(address & 0x07FF) as usize],
+
+            // PRG-RAM
+            0x6000...0x7FFF if self.prg_ram_enable() => {
+                let len = cartridge.prg_ram.len();
+                cartridge.prg_ram[(address - 0x6000) as usize % len]
+            }
+
+            // PRG-ROM
+            0x8000...0xFFFF => cartridge.prg_rom[self.prg_rom_index(cartridge, address)],
+
+            _ => {
+                return false;
+            }
+        };
+
+        true
+    }
+
+    fn cpu_write(&mut self,
+                 ram: &mut [u8],
+                 cartridge: &mut Cartridge,
+                 address: u16,
+                 value: u8)
+                 -> bool {
+        match address {
+            // Internal RAM (2KiB; mirrored 3 times)
+            0x0000...0x1FFF => {
+                ram[(address & 0x07FF) as usize] = value;
+            }
+
+            // PRG-RAM
+            0x6000...0x7FFF => {
+                if self.prg_ram_enable() {
+                    let len = cartridge.prg_ram.len();
+                    cartridge.prg_ram[(address - 0x6000) as usize % len] = value;
+                }
+            }
+
+            // Load register (serial port)
+            0x8000...0xFFFF => {
+                if value & 0x80 != 0 {
+                    // Reset shift register and fix the last PRG-ROM bank at $C000
+                    self.shift = 0;
+                    self.shift_count = 0;
+                    self.control |= 0x0C;
+                } else {
+                    self.shift = (self.shift >> 1) | ((value & 1) << 4);
+                    self.shift_count += 1;
+
+                    if self.shift_count == 5 {
+                        let r = self.shift;
+                        self.write_register(address, r);
+
+                        self.shift = 0;
+                        self.shift_count = 0;
+                    }
+                }
+            }
+
+            _ => {
+                return false;
+            }
+        }
+
+        true
+    }
+
+    fn ppu_read(&mut self,
+                ram: &mut [u8],
+                palette: &mut [u8],
+                cartridge: &mut Cartridge,
+                address: u16,
+                ptr: &mut u8)
+                -> bool {
+        *ptr = match address {
+            // CHR-ROM
+            0x0000...0x1FFF => cartridge.chr[self.chr_index(cartridge, address)],
+
+            // Internal RAM (2KiB; mirrored)
+            0x2000...0x3EFF => ram[self.mirroring().nametable_index(address)],
+
+            // Palette RAM
+            0x3F00...0x3F1F => palette[palette_index(address)],
+
+            _ => {
+                return false;
+            }
+        };
+
+        true
+    }
+
+    fn ppu_write(&mut self,
+                 ram: &mut [u8],
+                 palette: &mut [u8],
+                 cartridge: &mut Cartridge,
+                 address: u16,
+                 value: u8)
+                 -> bool {
+        match address {
+            // CHR-RAM
+            0x0000...0x1FFF if cartridge.chr_mutable => {
+                let index = self.chr_index(cartridge, address);
+                cartridge.chr[index] = value;
+            }
+
+            // Internal RAM (2KiB; mirrored)
+            0x2000...0x3EFF => {
+                ram[self.mirroring().nametable_index(address)] = value;
+            }
+
+            // Palette RAM
+            0x3F00...0x3F1F => {
+                palette[palette_index(address)] = value;
+            }
+
+            _ => {
+                return false;
+            }
+        }
+
+        true
+    }
+}
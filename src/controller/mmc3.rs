@@ -0,0 +1,483 @@
+use cartridge::Cartridge;
+use state::State;
+use super::{Controller, Mirroring, palette_index};
+
+/// MMC3 (iNES Mapper 4)
+///
+/// Switches PRG-ROM in 8 KiB banks and CHR in 1 KiB and 2 KiB banks. Contains a scanline
+/// counter that is clocked by rising edges of PPU A12 (which normally happen once per
+/// scanline when the background and sprites use different pattern tables) and asserts
+/// IRQ when it reaches zero.
+#[derive(Default)]
+pub struct MMC3 {
+    /// [$8000-$9FFE, even] Bank select
+    ///
+    /// 76543210
+    /// |||||+++- bank register to update on next write to $8001 (R0 to R7)
+    /// |+------- PRG-ROM bank mode (0: $8000 swappable and $C000 fixed to second-last
+    /// |                            bank; 1: $C000 swappable and $8000 fixed)
+    /// +-------- CHR A12 inversion (0: two 2 KiB banks at $0000 and four 1 KiB banks at
+    ///                              $1000; 1: the reverse)
+    ///
+    bank_select: u8,
+
+    /// [$8001-$9FFF, odd] Bank registers (R0 to R7)
+    banks: [u8; 8],
+
+    /// [$A000-$BFFE, even] Mirroring (0: vertical; 1: horizontal)
+    mirroring: u8,
+
+    /// [$A001-$BFFF, odd] PRG-RAM protect (bit 7: enable; bit 6: deny writes)
+    prg_ram_protect: u8,
+
+    /// [$C000-$DFFE, even] IRQ latch (value to reload the counter with)
+    irq_latch: u8,
+
+    /// IRQ counter (scanline counter)
+    irq_counter: u8,
+
+    /// Set by a write to [$C001-$DFFF, odd]; the counter is reloaded on the next clock
+    irq_reload: bool,
+
+    /// [$E000-$FFFF] IRQ enable (even addresses disable and acknowledge; odd enable)
+    irq_enable: bool,
+
+    /// IRQ (signal); held until acknowledged by a write to $E000
+    irq_occurred: bool,
+
+    /// Last observed state of PPU A12
+    a12: bool,
+
+    /// Number of CPU cycles (M2) that PPU A12 has been low; A12 must be low for a few
+    /// cycles before a rising edge is counted (this filters out the rapid toggling
+    /// of A12 during the sprite fetches in 8x16 mode)
+    a12_low_cycles: u8,
+}
+
+impl MMC3 {
+    fn prg_rom_index(&self, cartridge: &Cartridge, address: u16) -> usize {
+        let banks = cartridge.prg_rom.len() / 0x2000;
+        let last = banks.saturating_sub(1);
+        let second_last = banks.saturating_sub(2);
+
+        let bank = match (address >> 13) & 3 {
+            // $8000-$9FFF
+            0 => {
+                if self.bank_select & 0x40 == 0 {
+                    self.banks[6] as usize
+                } else {
+                    second_last
+                }
+            }
+
+            // $A000-$BFFF
+            1 => self.banks[7] as usize,
+
+            // $C000-$DFFF
+            2 => {
+                if self.bank_select & 0x40 == 0 {
+                    second_last
+                } else {
+                    self.banks[6] as usize
+                }
+            }
+
+            // $E000-$FFFF (fixed to the last bank)
+            _ => last,
+        };
+
+        (bank % banks) * 0x2000 + (address as usize & 0x1FFF)
+    }
+
+    fn chr_index(&self, cartridge: &Cartridge, address: u16) -> usize {
+        // CHR A12 inversion swaps the halves of the pattern tables
+        let address = if self.bank_select & 0x80 != 0 {
+            address ^ 0x1000
+        } else {
+            address
+        };
+
+        // NOTE: Banks are in units of 1 KiB; R0 and R1 are 2 KiB banks that ignore bit 0
+        let bank = match address >> 10 {
+            0 => (self.banks[0] & 0xFE) as usize,
+            1 => (self.banks[0] | 0x01) as usize,
+            2 => (self.banks[1] & 0xFE) as usize,
+            3 => (self.banks[1] | 0x01) as usize,
+            n => self.banks[(n - 2) as usize] as usize,
+        };
+
+        let banks = cartridge.chr.len() / 0x400;
+
+        (bank % banks) * 0x400 + (address as usize & 0x3FF)
+    }
+
+    fn mirroring(&self) -> Mirroring {
+        if self.mirroring & 1 == 0 {
+            Mirroring::Vertical
+        } else {
+            Mirroring::Horizontal
+        }
+    }
+
+    /// Observe an address placed on the PPU address bus.
+    fn observe(&mut self, address: u16) {
+        // NOTE: Palette RAM is internal to the PPU and never reaches the cartridge
+        if address >= 0x3F00 {
+            return;
+        }
+
+        let a12 = address & 0x1000 != 0;
+        if a12 && !self.a12 && self.a12_low_cycles >= 3 {
+            // Rising edge of A12
+            self.clock_irq_counter();
+        }
+
+        if a12 {
+            self.a12_low_cycles = 0;
+        }
+
+        self.a12 = a12;
+    }
+
+    fn clock_irq_counter(&mut self) {
+        if self.irq_counter == 0 || self.irq_reload {
+            self.irq_counter = self.irq_latch;
+            self.irq_reload = false;
+        } else {
+            self.irq_counter -= 1;
+        }
+
+        if self.irq_counter == 0 && self.irq_enable {
+            self.irq_occurred = true;
+        }
+    }
+
+    fn write_register(&mut self, address: u16, value: u8) {
+        let even = address & 1 == 0;
+        match address {
+            0x8000...0x9FFF if even => {
+                self.bank_select = value;
+            }
+
+            0x8000...0x9FFF => {
+                self.banks[(self.bank_select & 7) as usize] = value;
+            }
+
+            0xA000...0xBFFF if even => {
+                self.mirroring = value;
+            }
+
+            0xA000...0xBFFF => {
+                self.prg_ram_protect = value;
+            }
+
+            0xC000...0xDFFF if even => {
+                self.irq_latch = value;
+            }
+
+            0xC000...0xDFFF => {
+                self.irq_counter = 0;
+                self.irq_reload = true;
+            }
+
+            0xE000...0xFFFF if even => {
+                self.irq_enable = false;
+                self.irq_occurred = false;
+            }
+
+            0xE000...0xFFFF => {
+                self.irq_enable = true;
+            }
+
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl Controller for MMC3 {
+    fn reset(&mut self) {
+        self.bank_select = 0;
+        self.banks = [0, 2, 4, 5, 6, 7, 0, 1];
+        self.mirroring = 0;
+        self.prg_ram_protect = 0x80;
+
+        self.irq_latch = 0;
+        self.irq_counter = 0;
+        self.irq_reload = false;
+        self.irq_enable = false;
+        self.irq_occurred = false;
+
+        self.a12 = false;
+        self.a12_low_cycles = 0;
+    }
+
+    fn sync(&mut self, s: &mut State) {
+        s.u8(&mut self.bank_select);
+        s.slice(&mut self.banks);
+        s.u8(&mut self.mirroring);
+        s.u8(&mut self.prg_ram_protect);
+        s.u8(&mut self.irq_latch);
+        s.u8(&mut self.irq_counter);
+        s.bool(&mut self.irq_reload);
+        s.bool(&mut self.irq_enable);
+        s.bool(&mut self.irq_occurred);
+        s.bool(&mut self.a12);
+        s.u8(&mut self.a12_low_cycles);
+    }
+
+    fn step(&mut self) {
+        if !self.a12 && self.a12_low_cycles < 0xFF {
+            self.a12_low_cycles += 1;
+        }
+    }
+
+    fn irq(&self) -> bool {
+        self.irq_occurred
+    }
+
+    fn cpu_read(&mut self,
+                ram: &mut [u8],
+                cartridge: &mut Cartridge,
+                address: u16,
+                ptr: &mut u8)
+                -> bool {
+        *ptr = match address {
+            // Internal RAM (2KiB; mirrored 3 times)
+            0x0000...0x1FFF => ram[(address & 0x07FF) as usize],
+
+            // PRG-RAM
+            0x6000...0x7FFF if self.prg_ram_protect & 0x80 != 0 => {
+                cartridge.prg_ram[(address - 0x6000) as usize]
+            }
+
+            // PRG-ROM
+            0x8000...0xFFFF => cartridge.prg_rom[self.prg_rom_index(cartridge, address)],
+
+            _ => {
+                return false;
+            }
+        };
+
+        true
+    }
+
+    fn cpu_write(&mut self,
+                 ram: &mut [u8],
+                 cartridge: &mut Cartridge,
+                 address: u16,
+                 value: u8)
+                 -> bool {
+        match address {
+            // Internal RAM (2KiB; mirrored 3 times)
+            0x0000...0x1FFF => {
+                ram[(address & 0x07FF) as usize] = value;
+            }
+
+            // PRG-RAM
+            0x6000...0x7FFF => {
+                if self.prg_ram_protect & 0xC0 == 0x80 {
+                    cartridge.prg_ram[(address - 0x6000) as usize] = value;
+                }
+            }
+
+            // Registers
+            0x8000...0xFFFF => {
+                self.write_register(address, value);
+            }
+
+            _ => {
+                return false;
+            }
+        }
+
+        true
+    }
+
+    fn ppu_read(&mut self,
+                ram: &mut [u8],
+                palette: &mut [u8],
+                cartridge: &mut Cartridge,
+                address: u16,
+                ptr: &mut u8)
+                -> bool {
+        self.observe(address);
+
+        *ptr = match address {
+            // CHR-ROM
+            0x0000...0x1FFF => cartridge.chr[self.chr_index(cartridge, address)],
+
+            // Internal RAM (2KiB; mirrored)
+            0x2000...0x3EFF => ram[self.mirroring().nametable_index(address)],
+
+            // Palette RAM
+            0x3F00...0x3F1F => palette[palette_index(address)],
+
+            _ => {
+                return false;
+            }
+        };
+
+        true
+    }
+
+    fn ppu_write(&mut self,
+                 ram: &mut [u8],
+                 palette: &mut [u8],
+                 cartridge: &mut Cartridge,
+                 address: u16,
+                 value: u8)
+                 -> bool {
+        self.observe(address);
+
+        match address {
+            // CHR-RAM
+            0x0000...0x1FFF if cartridge.chr_mutable => {
+                let index = self.chr_index(cartridge, address);
+                cartridge.chr[index] = value;
+            }
+
+            // Internal RAM (2KiB; mirrored)
+            0x2000...0x3EFF => {
+                ram[self.mirroring().nametable_index(address)] = value;
+            }
+
+            // Palette RAM
+            0x3F00...0x3F1F => {
+                palette[palette_index(address)] = value;
+            }
+
+            _ => {
+                return false;
+            }
+        }
+
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cartridge::Cartridge;
+    use controller::Controller;
+    use super::MMC3;
+
+    /// MMC3 (after reset) with 32 KiB of PRG-ROM and 8 KiB of CHR-ROM
+    fn setup() -> (MMC3, Cartridge) {
+        let mut cartridge: Cartridge = Default::default();
+        cartridge.prg_rom = vec![0; 0x8000];
+        cartridge.prg_ram = vec![0; 0x2000];
+        cartridge.chr = vec![0; 0x2000];
+
+        let mut mmc3: MMC3 = Default::default();
+        mmc3.reset();
+
+        (mmc3, cartridge)
+    }
+
+    fn write(mmc3: &mut MMC3, cartridge: &mut Cartridge, address: u16, value: u8) {
+        let mut ram = vec![0; 0x800];
+        mmc3.cpu_write(&mut ram, cartridge, address, value);
+    }
+
+    fn fetch(mmc3: &mut MMC3, cartridge: &mut Cartridge, address: u16) {
+        let mut ram = vec![0; 0x800];
+        let mut palette = vec![0; 0x20];
+        let mut value = 0;
+        mmc3.ppu_read(&mut ram, &mut palette, cartridge, address, &mut value);
+    }
+
+    /// Rising edge of A12 after it was low for `low` CPU cycles
+    fn edge(mmc3: &mut MMC3, cartridge: &mut Cartridge, low: usize) {
+        fetch(mmc3, cartridge, 0x0000);
+        for _ in 0..low {
+            mmc3.step();
+        }
+
+        fetch(mmc3, cartridge, 0x1000);
+    }
+
+    /// Latch of `latch`, reloaded, and IRQ enabled
+    fn irq(latch: u8) -> (MMC3, Cartridge) {
+        let (mut mmc3, mut cartridge) = setup();
+        write(&mut mmc3, &mut cartridge, 0xC000, latch);
+        write(&mut mmc3, &mut cartridge, 0xC001, 0);
+        write(&mut mmc3, &mut cartridge, 0xE001, 0);
+
+        (mmc3, cartridge)
+    }
+
+    #[test]
+    fn counter() {
+        // Reloaded with 3 on the first edge; IRQ on the edge that takes it to 0
+        let (mut mmc3, mut cartridge) = irq(3);
+        for _ in 0..3 {
+            edge(&mut mmc3, &mut cartridge, 3);
+            assert!(!mmc3.irq());
+        }
+
+        edge(&mut mmc3, &mut cartridge, 3);
+        assert!(mmc3.irq());
+        assert_eq!(mmc3.irq_counter, 0);
+
+        // Held until acknowledged by $E000 (which also disables IRQ)
+        edge(&mut mmc3, &mut cartridge, 3);
+        assert!(mmc3.irq());
+        write(&mut mmc3, &mut cartridge, 0xE000, 0);
+        assert!(!mmc3.irq());
+
+        for _ in 0..8 {
+            edge(&mut mmc3, &mut cartridge, 3);
+            assert!(!mmc3.irq());
+        }
+    }
+
+    #[test]
+    fn latch_zero() {
+        // The counter is reloaded with 0 on every edge; IRQ on each
+        let (mut mmc3, mut cartridge) = irq(0);
+        for _ in 0..4 {
+            edge(&mut mmc3, &mut cartridge, 3);
+            assert!(mmc3.irq());
+
+            write(&mut mmc3, &mut cartridge, 0xE000, 0);
+            write(&mut mmc3, &mut cartridge, 0xE001, 0);
+        }
+    }
+
+    #[test]
+    fn filter() {
+        // A12 must be low for 3 CPU cycles before an edge is counted
+        let (mut mmc3, mut cartridge) = irq(1);
+        edge(&mut mmc3, &mut cartridge, 3);
+        assert_eq!(mmc3.irq_counter, 1);
+
+        for _ in 0..8 {
+            edge(&mut mmc3, &mut cartridge, 2);
+        }
+        assert_eq!(mmc3.irq_counter, 1);
+
+        edge(&mut mmc3, &mut cartridge, 3);
+        assert_eq!(mmc3.irq_counter, 0);
+        assert!(mmc3.irq());
+    }
+
+    #[test]
+    fn a12_high() {
+        // Reads that keep A12 high (or that never reach the cartridge, like palette RAM) are
+        // not edges
+        let (mut mmc3, mut cartridge) = irq(1);
+        edge(&mut mmc3, &mut cartridge, 3);
+
+        for _ in 0..8 {
+            fetch(&mut mmc3, &mut cartridge, 0x3F00);
+            for _ in 0..3 {
+                mmc3.step();
+            }
+
+            fetch(&mut mmc3, &mut cartridge, 0x1FF0);
+        }
+
+        assert_eq!(mmc3.irq_counter, 1);
+        assert!(!mmc3.irq());
+    }
+}
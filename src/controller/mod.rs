@@ -0,0 +1,372 @@
+mod nrom;
+mod mmc1;
+mod uxrom;
+mod cnrom;
+mod mmc3;
+mod axrom;
+mod color_dreams;
+mod gxrom;
+
+use std::cmp;
+
+use cartridge::Cartridge;
+use state::State;
+
+pub use self::nrom::NROM;
+pub use self::mmc1::MMC1;
+pub use self::uxrom::UxROM;
+pub use self::cnrom::CNROM;
+pub use self::mmc3::MMC3;
+pub use self::axrom::AxROM;
+pub use self::color_dreams::ColorDreams;
+pub use self::gxrom::GxROM;
+
+/// Memory Management Controller (commonly known as MMC or mapper) contained within each
+/// game cartridge
+pub trait Controller {
+    fn reset(&mut self);
+
+    /// Clocked once for each CPU cycle (M2).
+    fn step(&mut self) {}
+
+    /// State of the IRQ line from the mapper (level-triggered; true = asserted).
+    fn irq(&self) -> bool {
+        false
+    }
+
+    /// Save (or load) the state of the mapper (its registers).
+    fn sync(&mut self, s: &mut State);
+
+    /// The mapper _attempts_ to read address (from CPU). If unmapped, return false. If mapped,
+    /// insert value at `ptr` and return true.
+    fn cpu_read(&mut self,
+                ram: &mut [u8],
+                cartridge: &mut Cartridge,
+                address: u16,
+                ptr: &mut u8)
+                -> bool;
+
+    /// The mapper _attempts_ to write address (to CPU). If unmapped, return false;
+    /// otherwise, true.
+    fn cpu_write(&mut self,
+                 ram: &mut [u8],
+                 cartridge: &mut Cartridge,
+                 address: u16,
+                 value: u8)
+                 -> bool;
+
+    /// The mapper _attempts_ to read address (from PPU). If unmapped, return false. If mapped,
+    /// insert value at `ptr` and return true.
+    fn ppu_read(&mut self,
+                ram: &mut [u8],
+                palette: &mut [u8],
+                cartridge: &mut Cartridge,
+                address: u16,
+                ptr: &mut u8)
+                -> bool;
+
+    /// The mapper _attempts_ to write address (to PPU). If unmapped, return false;
+    /// otherwise, true.
+    fn ppu_write(&mut self,
+                 ram: &mut [u8],
+                 palette: &mut [u8],
+                 cartridge: &mut Cartridge,
+                 address: u16,
+                 value: u8)
+                 -> bool;
+}
+
+/// Board made of discrete logic (UxROM, CNROM, AxROM, GxROM, Color Dreams): a single bank
+/// register written at $8000-$FFFF, PRG-RAM at $6000-$7FFF, and CHR-ROM (or RAM) at
+/// $0000-$1FFF. Only the meaning of the bank register differs between boards; the rest of
+/// the `Controller` is shared (see below).
+pub trait Discrete {
+    /// [$8000-$FFFF] Bank register
+    fn bank(&mut self) -> &mut u8;
+
+    /// The value written to the bank register is AND'ed with the value read from PRG-ROM
+    /// at the same address
+    fn bus_conflicts(&self) -> bool {
+        true
+    }
+
+    /// Translate a PRG-ROM address ($8000-$FFFF) into an index of PRG-ROM.
+    fn prg_rom_index(&self, cartridge: &Cartridge, address: u16) -> usize;
+
+    /// Translate a CHR address ($0000-$1FFF) into an index of CHR (not banked by default).
+    fn chr_index(&self, _: &Cartridge, address: u16) -> usize {
+        address as usize
+    }
+
+    /// Nametable mirroring (hard-wired on the cartridge by default)
+    fn mirroring(&self, cartridge: &Cartridge) -> Mirroring {
+        Mirroring::from_cartridge(cartridge)
+    }
+}
+
+impl<T: Discrete> Controller for T {
+    fn reset(&mut self) {
+        *self.bank() = 0;
+    }
+
+    fn sync(&mut self, s: &mut State) {
+        s.u8(self.bank());
+    }
+
+    fn cpu_read(&mut self,
+                ram: &mut [u8],
+                cartridge: &mut Cartridge,
+                address: u16,
+                ptr: &mut u8)
+                -> bool {
+        *ptr = match address {
+            // Internal RAM (2KiB; mirrored 3 times)
+            0x0000...0x1FFF => ram[(address & 0x07FF) as usize],
+
+            // PRG-RAM
+            0x6000...0x7FFF => cartridge.prg_ram[(address - 0x6000) as usize],
+
+            // PRG-ROM
+            0x8000...0xFFFF => cartridge.prg_rom[self.prg_rom_index(cartridge, address)],
+
+            _ => {
+                return false;
+            }
+        };
+
+        true
+    }
+
+    fn cpu_write(&mut self,
+                 ram: &mut [u8],
+                 cartridge: &mut Cartridge,
+                 address: u16,
+                 value: u8)
+                 -> bool {
+        match address {
+            // Internal RAM (2KiB; mirrored 3 times)
+            0x0000...0x1FFF => {
+                ram[(address & 0x07FF) as usize] = value;
+            }
+
+            // PRG-RAM
+            0x6000...0x7FFF => {
+                cartridge.prg_ram[(address - 0x6000) as usize] = value;
+            }
+
+            // Bank select
+            0x8000...0xFFFF => {
+                let value = if self.bus_conflicts() {
+                    value & cartridge.prg_rom[self.prg_rom_index(cartridge, address)]
+                } else {
+                    value
+                };
+
+                *self.bank() = value;
+            }
+
+            _ => {
+                return false;
+            }
+        }
+
+        true
+    }
+
+    fn ppu_read(&mut self,
+                ram: &mut [u8],
+                palette: &mut [u8],
+                cartridge: &mut Cartridge,
+                address: u16,
+                ptr: &mut u8)
+                -> bool {
+        *ptr = match address {
+            // CHR-ROM
+            0x0000...0x1FFF => cartridge.chr[self.chr_index(cartridge, address)],
+
+            // Internal RAM (2KiB; mirrored)
+            0x2000...0x3EFF => ram[self.mirroring(cartridge).nametable_index(address)],
+
+            // Palette RAM
+            0x3F00...0x3F1F => palette[palette_index(address)],
+
+            _ => {
+                return false;
+            }
+        };
+
+        true
+    }
+
+    fn ppu_write(&mut self,
+                 ram: &mut [u8],
+                 palette: &mut [u8],
+                 cartridge: &mut Cartridge,
+                 address: u16,
+                 value: u8)
+                 -> bool {
+        match address {
+            // CHR-RAM
+            0x0000...0x1FFF if cartridge.chr_mutable => {
+                let index = self.chr_index(cartridge, address);
+                cartridge.chr[index] = value;
+            }
+
+            // Internal RAM (2KiB; mirrored)
+            0x2000...0x3EFF => {
+                ram[self.mirroring(cartridge).nametable_index(address)] = value;
+            }
+
+            // Palette RAM
+            0x3F00...0x3F1F => {
+                palette[palette_index(address)] = value;
+            }
+
+            _ => {
+                return false;
+            }
+        }
+
+        true
+    }
+}
+
+/// Nametable mirroring (arrangement of the 2 KiB of internal V-RAM into the 4 nametables)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Mirroring {
+    /// $2000 = $2400 and $2800 = $2C00
+    Horizontal,
+
+    /// $2000 = $2800 and $2400 = $2C00
+    Vertical,
+
+    /// All nametables are the first 1 KiB of V-RAM
+    SingleScreenLo,
+
+    /// All nametables are the second 1 KiB of V-RAM
+    SingleScreenHi,
+}
+
+impl Mirroring {
+    /// Mirroring as hard-wired on the cartridge (from the iNES header)
+    pub fn from_cartridge(cartridge: &Cartridge) -> Mirroring {
+        if cartridge.vram_mirroring == 1 {
+            Mirroring::Vertical
+        } else {
+            Mirroring::Horizontal
+        }
+    }
+
+    /// Translate a nametable address ($2000-$3EFF) into an index of internal V-RAM.
+    pub fn nametable_index(&self, address: u16) -> usize {
+        let address = (address & 0xFFF) as usize;
+        let table = address >> 10;
+        let page = match *self {
+            Mirroring::Horizontal => table >> 1,
+            Mirroring::Vertical => table & 1,
+            Mirroring::SingleScreenLo => 0,
+            Mirroring::SingleScreenHi => 1,
+        };
+
+        (page << 10) | (address & 0x3FF)
+    }
+}
+
+/// Index of `address` in bank `bank` (of `size` bytes) of `data` (PRG-ROM or CHR); the bank
+/// number wraps around at the number of banks and data smaller than a bank is mirrored
+/// within it (a 16 KiB PRG-ROM on a board that switches 32 KiB at a time).
+pub fn bank_index(data: &[u8], size: usize, bank: usize, address: u16) -> usize {
+    let banks = cmp::max(data.len() / size, 1);
+
+    ((bank % banks) * size + (address as usize & (size - 1))) % data.len()
+}
+
+/// Translate a palette address ($3F00-$3F1F) into an index of palette RAM.
+/// NOTE: $3F10, $3F14, $3F18, and $3F1C are mirrors of $3F00, $3F04, $3F08, and $3F0C.
+pub fn palette_index(address: u16) -> usize {
+    let index = (address & 0x1F) as usize;
+    if index & 0x13 == 0x10 {
+        index & 0x0F
+    } else {
+        index
+    }
+}
+
+/// iNES mapper numbers of the supported mappers (see `from_cartridge`)
+pub const SUPPORTED: [u16; 8] = [0, 1, 2, 3, 4, 7, 11, 66];
+
+pub fn from_cartridge(cartridge: &Cartridge) -> Box<Controller> {
+    match cartridge.ines_mapper {
+        0 => Box::new(NROM {}),
+        1 => Box::new(MMC1::default()),
+        2 => Box::new(UxROM::default()),
+        3 => Box::new(CNROM::default()),
+        4 => Box::new(MMC3::default()),
+        7 => Box::new(AxROM::default()),
+        11 => Box::new(ColorDreams::default()),
+        66 => Box::new(GxROM::default()),
+
+        _ => {
+            panic!("unknown/unsupported iNES Mapper: {}", cartridge.ines_mapper);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use cartridge::Cartridge;
+    use super::{SUPPORTED, from_cartridge};
+
+    /// Smallest image the iNES header allows (16 KiB of PRG-ROM; 8 KiB of CHR-ROM or none)
+    fn cartridge(mapper: u16, chr_rom: bool) -> Cartridge {
+        let chr_banks = if chr_rom { 1 } else { 0 };
+        let mut image = vec![0x4E, 0x45, 0x53, 0x1A, 1, chr_banks, (mapper as u8) << 4,
+                             (mapper as u8) & 0xF0, 0, 0, 0, 0, 0, 0, 0, 0];
+
+        image.extend(vec![0xFF; 0x4000 + chr_banks as usize * 0x2000]);
+
+        let mut cartridge: Cartridge = Default::default();
+        cartridge.read(&mut Cursor::new(image)).unwrap();
+
+        cartridge
+    }
+
+    #[test]
+    fn smallest_image_with_any_bank() {
+        for &mapper in SUPPORTED.iter() {
+            for &chr_rom in &[true, false] {
+                let mut cartridge = cartridge(mapper, chr_rom);
+                let mut controller = from_cartridge(&cartridge);
+                let mut ram = vec![0; 0x800];
+                let mut palette = vec![0; 0x20];
+                let mut value = 0;
+
+                controller.reset();
+
+                for &bank in &[0x00, 0xFF, 0x55, 0xAA, 0x0F, 0xF0, 0x87] {
+                    // Every register of every mapper (some are written a bit at a time)
+                    for register in 0..16 {
+                        let address = 0x8000 + (register >> 1) * 0x1000 + (register & 1);
+                        for _ in 0..5 {
+                            controller.cpu_write(&mut ram, &mut cartridge, address, bank);
+                        }
+                    }
+
+                    for address in 0x6000..0x10000 {
+                        controller.cpu_read(&mut ram, &mut cartridge, address as u16, &mut value);
+                    }
+
+                    for address in 0x0000..0x3F20 {
+                        controller.ppu_read(&mut ram,
+                                            &mut palette,
+                                            &mut cartridge,
+                                            address,
+                                            &mut value);
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,129 @@
+use cartridge::Cartridge;
+use state::State;
+use super::{Controller, Mirroring, palette_index};
+
+#[derive(Default)]
+pub struct NROM {
+}
+
+impl Controller for NROM {
+    fn reset(&mut self) {}
+
+    fn sync(&mut self, _: &mut State) {}
+
+    fn cpu_read(&mut self,
+                ram: &mut [u8],
+                cartridge: &mut Cartridge,
+                address: u16,
+                ptr: &mut u8)
+                -> bool {
+        *ptr = match address {
+            // Internal RAM (2KiB; mirrored 3 times)
+            0x0000...0x1FFF => ram[(address & 0x07FF) as usize],
+
+            // PRG-RAM
+            0x6000...0x7FFF => cartridge.prg_ram[(address - 0x6000) as usize],
+
+            // PRG-ROM #1
+            0x8000...0xBFFF => cartridge.prg_rom[(address - 0x8000) as usize],
+
+            // PRG-ROM #2
+            0xC000...0xFFFF => {
+                if cartridge.prg_rom.len() <= 0x4000 {
+                    // 16-KiB PRG-ROM; mirror of 0x8000...0xBFFF
+                    cartridge.prg_rom[(address - 0xC000) as usize]
+                } else {
+                    // 32-KiB PRG-ROM
+                    cartridge.prg_rom[(address - 0x8000) as usize]
+                }
+            }
+
+            _ => {
+                return false;
+            }
+        };
+
+        true
+    }
+
+    fn cpu_write(&mut self,
+                 ram: &mut [u8],
+                 cartridge: &mut Cartridge,
+                 address: u16,
+                 value: u8)
+                 -> bool {
+        match address {
+            // Internal RAM (2KiB; mirrored 3 times)
+            0x0000...0x1FFF => {
+                ram[(address & 0x07FF) as usize] = value;
+            }
+
+            // PRG-RAM
+            0x6000...0x7FFF => {
+                cartridge.prg_ram[(address - 0x6000) as usize] = value;
+            }
+
+            _ => {
+                return false;
+            }
+        }
+
+        true
+    }
+
+    fn ppu_read(&mut self,
+                ram: &mut [u8],
+                palette: &mut [u8],
+                cartridge: &mut Cartridge,
+                address: u16,
+                ptr: &mut u8)
+                -> bool {
+        *ptr = match address {
+            // CHR-ROM
+            0x0000...0x1FFF => cartridge.chr[address as usize],
+
+            // Internal RAM (2KiB; mirrored)
+            0x2000...0x3EFF => ram[Mirroring::from_cartridge(cartridge).nametable_index(address)],
+
+            // Palette RAM
+            0x3F00...0x3F1F => palette[palette_index(address)],
+
+            _ => {
+                return false;
+            }
+        };
+
+        true
+    }
+
+    fn ppu_write(&mut self,
+                 ram: &mut [u8],
+                 palette: &mut [u8],
+                 cartridge: &mut Cartridge,
+                 address: u16,
+                 value: u8)
+                 -> bool {
+        match address {
+            // CHR-RAM
+            0x0000...0x1FFF if cartridge.chr_mutable => {
+                cartridge.chr[address as usize] = value;
+            }
+
+            // Internal RAM (2KiB; mirrored)
+            0x2000...0x3EFF => {
+                ram[Mirroring::from_cartridge(cartridge).nametable_index(address)] = value;
+            }
+
+            // Palette RAM
+            0x3F00...0x3F1F => {
+                palette[palette_index(address)] = value;
+            }
+
+            _ => {
+                return false;
+            }
+        }
+
+        true
+    }
+}
@@ -0,0 +1,29 @@
+use cartridge::Cartridge;
+use super::{Discrete, bank_index};
+
+/// UxROM (iNES Mapper 2)
+///
+/// Switches a 16 KiB PRG-ROM bank at $8000 with the last bank fixed at $C000. CHR is
+/// (almost always) 8 KiB of RAM. Has bus conflicts.
+#[derive(Default)]
+pub struct UxROM {
+    /// [$8000-$FFFF] PRG-ROM bank (16 KiB at $8000)
+    prg_bank: u8,
+}
+
+impl Discrete for UxROM {
+    fn bank(&mut self) -> &mut u8 {
+        &mut self.prg_bank
+    }
+
+    fn prg_rom_index(&self, cartridge: &Cartridge, address: u16) -> usize {
+        let bank = if address < 0xC000 {
+            self.prg_bank as usize
+        } else {
+            // Fixed to the last bank
+            (cartridge.prg_rom.len() / 0x4000).saturating_sub(1)
+        };
+
+        bank_index(&cartridge.prg_rom, 0x4000, bank, address)
+    }
+}
@@ -4,6 +4,7 @@ mod op;
 mod operation;
 mod table;
 use bus::Bus;
+use state::State;
 
 // Generate memory Controller trait for CPU
 make_controller!();
@@ -33,6 +34,41 @@ impl Flags {
     }
 }
 
+/// Behavior of the CPU when executing one of the KIL (JAM) operations
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum JamBehavior {
+    /// Halt the CPU (as in hardware) until the next reset; the rest of the machine
+    /// keeps running
+    Halt,
+
+    /// Panic (useful when debugging)
+    Panic,
+}
+
+impl Default for JamBehavior {
+    fn default() -> Self {
+        JamBehavior::Halt
+    }
+}
+
+/// Snapshot of the CPU registers
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Registers {
+    pub a: u8,
+    pub x: u8,
+    pub y: u8,
+    pub pc: u16,
+    pub s: u8,
+    pub p: u8,
+}
+
+/// Interrupt taken by the CPU
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Interrupt {
+    NMI,
+    IRQ,
+}
+
 /// CPU Registers and other misc. contextual information required by operations
 #[derive(Default)]
 pub struct Context {
@@ -56,9 +92,40 @@ pub struct Context {
 
     // Running clock cycle counter (used for reference in debug)
     total_cycles: u32,
+
+    // Interrupt polling: NMI is pending (sampled at the start of each cycle)
+    nmi_poll: bool,
+
+    // Interrupt polling: IRQ is asserted _and_ not masked by I (sampled at the start of
+    // each cycle)
+    irq_poll: bool,
+
+    // Behavior of KIL (JAM)
+    pub jam_behavior: JamBehavior,
+
+    // Set when the CPU is halted by KIL (JAM); cleared on reset
+    pub jammed: bool,
+
+    // Interrupt whose vector was taken by the last interrupt sequence (none for a BRK that
+    // was not hijacked)
+    taken: Option<Interrupt>,
 }
 
 impl Context {
+    /// Reset (soft); registers other than S and I are unchanged and S is decremented by 3
+    /// (as if an interrupt sequence ran without writing to the stack)
+    fn soft_reset(&mut self, b: &mut Bus) {
+        self.s = self.s.wrapping_sub(3);
+        self.p.insert(IRQ_DISABLE);
+
+        self.nmi_poll = false;
+        self.irq_poll = false;
+
+        self.jammed = false;
+
+        self.pc = b.read(0xFFFC) as u16 | ((b.read(0xFFFD) as u16) << 8);
+    }
+
     fn reset(&mut self, b: &mut Bus) {
         self.a = 0;
         self.x = 0;
@@ -66,6 +133,11 @@ impl Context {
         self.s = 0xFD;
         self.p.bits = 0x34;
 
+        self.nmi_poll = false;
+        self.irq_poll = false;
+
+        self.jammed = false;
+
         // Get /RESET address
         // TODO: Should have helper methods in om to get vectors/16-bit data
         self.pc = b.read(0xFFFC) as u16 | ((b.read(0xFFFD) as u16) << 8);
@@ -73,8 +145,117 @@ impl Context {
     }
 
     pub fn step(&mut self, b: &mut Bus) {
+        self.halted(b);
+
+        // Poll for interrupts _before_ each cycle; this way, at the end of an instruction,
+        // the polled state is from the end of its second-to-last cycle (as in hardware).
+        // NOTE: This is also why changes to I from CLI, SEI, and PLP (done on their last
+        //       cycle) are delayed by an instruction while RTI is not.
+        self.poll(b);
+
         b.step();
-        self.total_cycles += 1;
+        self.total_cycles = self.total_cycles.wrapping_add(1);
+    }
+
+    fn poll(&mut self, b: &Bus) {
+        self.nmi_poll = b.nmi_occurred;
+        self.irq_poll = b.irq() && !self.p.contains(IRQ_DISABLE);
+    }
+
+    /// Count the cycles the CPU was halted for by DMC DMA (during its last read); the
+    /// halted CPU keeps repeating that read, so interrupts are polled through the halt
+    /// and the read completes after it.
+    fn halted(&mut self, b: &mut Bus) {
+        let cycles = b.take_dma_cycles();
+        if cycles > 0 {
+            self.total_cycles = self.total_cycles.wrapping_add(cycles);
+            self.poll(b);
+        }
+    }
+
+    /// Execute KIL (JAM)
+    pub fn jam(&mut self) {
+        // PC has already moved past the opcode
+        let pc = self.pc.wrapping_sub(1);
+
+        match self.jam_behavior {
+            JamBehavior::Halt => {
+                warn!("CPU jammed at ${:04X}", pc);
+
+                self.jammed = true;
+            }
+
+            JamBehavior::Panic => {
+                panic!("CPU jammed at ${:04X}", pc);
+            }
+        }
+    }
+
+    /// Interrupt sequence shared by BRK, IRQ, and NMI: push PC and P on the stack and jump
+    /// through the interrupt vector (the interrupt taken is kept for `run_next`).
+    pub fn interrupt(&mut self, b: &mut Bus, brk: bool) {
+        if !brk {
+            // Read next instruction byte (twice; PC is not incremented)
+            // NOTE: BRK fetches its padding byte during its normal opcode decode
+            self.step(b);
+            b.read(self.pc);
+
+            self.step(b);
+            b.read(self.pc);
+        }
+
+        // Push PCH on stack; decrement S
+        self.step(b);
+        b.write(0x100 + self.s as u16, (self.pc >> 8) as u8);
+        self.s = self.s.wrapping_sub(1);
+
+        // Push PCL on stack; decrement S
+        self.step(b);
+        b.write(0x100 + self.s as u16, self.pc as u8);
+        self.s = self.s.wrapping_sub(1);
+
+        // Push P on stack (with UNUSED set and BRK set only from BRK); decrement S
+        self.step(b);
+        let p = if brk { self.p | BREAK } else { self.p };
+        b.write(0x100 + self.s as u16, p.bits() | 0x20);
+        self.s = self.s.wrapping_sub(1);
+
+        // Select vector; an NMI that is detected by now will "hijack" the sequence of
+        // a BRK or IRQ (which will still push P as it normally would)
+        let vector = if self.nmi_poll {
+            // NMI was handled; unset flag
+            b.nmi_occurred = false;
+            self.taken = Some(Interrupt::NMI);
+
+            0xFFFA
+        } else {
+            self.taken = if brk { None } else { Some(Interrupt::IRQ) };
+
+            0xFFFE
+        };
+
+        // Fetch PCL; set the IRQ Disable flag
+        self.step(b);
+        let l = b.read(vector);
+        self.p.insert(IRQ_DISABLE);
+
+        // Fetch PCH
+        self.step(b);
+        let h = b.read(vector + 1);
+        self.pc = l as u16 | ((h as u16) << 8);
+    }
+
+    fn sync(&mut self, s: &mut State) {
+        s.u8(&mut self.a);
+        s.u8(&mut self.x);
+        s.u8(&mut self.y);
+        s.u16(&mut self.pc);
+        s.u8(&mut self.s);
+        s.u8(&mut self.p.bits);
+        s.u32(&mut self.total_cycles);
+        s.bool(&mut self.nmi_poll);
+        s.bool(&mut self.irq_poll);
+        s.bool(&mut self.jammed);
     }
 }
 
@@ -89,8 +270,40 @@ impl CPU {
         self.ctx.reset(b);
     }
 
-    /// Run Next Instruction
-    pub fn run_next(&mut self, b: &mut Bus) {
+    pub fn soft_reset(&mut self, b: &mut Bus) {
+        self.ctx.soft_reset(b);
+    }
+
+    pub fn registers(&self) -> Registers {
+        Registers {
+            a: self.ctx.a,
+            x: self.ctx.x,
+            y: self.ctx.y,
+            pc: self.ctx.pc,
+            s: self.ctx.s,
+            p: self.ctx.p.bits,
+        }
+    }
+
+    /// Running clock cycle counter (wraps around)
+    pub fn cycles(&self) -> u32 {
+        self.ctx.total_cycles
+    }
+
+    /// Run Next Instruction; followed by an interrupt sequence if one was polled during
+    /// the instruction. The interrupt is the one whose vector was taken: an NMI that hijacks
+    /// an IRQ (or a BRK) is reported as the NMI.
+    pub fn run_next(&mut self, b: &mut Bus) -> Option<Interrupt> {
+        if self.ctx.jammed {
+            // A jammed CPU no longer fetches instructions (or handles interrupts) but
+            // the clock still runs
+            self.ctx.step(b);
+
+            return None;
+        }
+
+        self.ctx.taken = None;
+
         // Fetch opcode
         let _pc = self.ctx.pc;
         self.ctx.step(b);
@@ -123,40 +336,27 @@ impl CPU {
             panic!(format!("unknown opcode ${:02X} at ${:04X}", opcode, _pc))
         }
 
-        // Check for pending NMI IRQ
-        if b.nmi_occurred {
-            trace!("NMI ------------------------------------------");
-
-            // Push PCH on stack; decrement S
-            self.ctx.step(b);
-            b.write(0x100 + self.ctx.s as u16, (self.ctx.pc >> 8) as u8);
-            self.ctx.s = self.ctx.s.wrapping_sub(1);
+        // The last read of the instruction may have been halted by DMC DMA
+        self.ctx.halted(b);
 
-            // Push PCL on stack; decrement S
-            self.ctx.step(b);
-            b.write(0x100 + self.ctx.s as u16, self.ctx.pc as u8);
-            self.ctx.s = self.ctx.s.wrapping_sub(1);
+        // Check for pending NMI or IRQ (as polled during the instruction)
+        if self.ctx.nmi_poll {
+            trace!("NMI ------------------------------------------");
 
-            // Push P on stack (with BRK and UNUSED set); decrement S
-            self.ctx.step(b);
-            b.write(0x100 + self.ctx.s as u16,
-                    (self.ctx.p | BREAK).bits() | 0x20);
-            self.ctx.s = self.ctx.s.wrapping_sub(1);
+            self.ctx.interrupt(b, false);
+        } else if self.ctx.irq_poll {
+            trace!("IRQ ------------------------------------------");
 
-            // Fetch PCL
-            self.ctx.step(b);
-            let l = b.read(0xFFFA);
+            self.ctx.interrupt(b, false);
+        }
 
-            // Fetch PCH
-            self.ctx.step(b);
-            let h = b.read(0xFFFB);
-            self.ctx.pc = l as u16 | ((h as u16) << 8);
+        // (as may the last read of the interrupt sequence)
+        self.ctx.halted(b);
 
-            // Set the IRQ Disable flag
-            self.ctx.p.insert(IRQ_DISABLE);
+        self.ctx.taken
+    }
 
-            // NMI was handled; unset flag
-            b.nmi_occurred = false;
-        }
+    pub fn sync(&mut self, s: &mut State) {
+        self.ctx.sync(s);
     }
 }
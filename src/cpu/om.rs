@@ -1,3 +1,4 @@
+use cpu;
 use bus::Bus;
 use super::Context;
 use super::Flags;
@@ -543,33 +544,16 @@ macro_rules! om_compare (($c:expr, $b:expr; $addr_mode:expr; $r:ident) => {
 
 macro_rules! om_adc (($c:expr, $b:expr; $addr_mode:expr) => {
     {
-        let value = $c.a as u16;
-        let operand = om::read($c, $b, $addr_mode) as u16;
-        let c = if $c.p.contains(cpu::CARRY) { 1 } else { 0 };
-        let r = value.wrapping_add(operand).wrapping_add(c);
-
-        $c.p.set(cpu::ZERO, (r & 0xFF) == 0);
-        $c.p.set(cpu::SIGN, r & 0x80 != 0);
-        $c.p.set(cpu::CARRY, ((r >> 8) & 1) != 0);
-        $c.p.set(cpu::OVERFLOW, ((value ^ r) & (operand ^ r) & 0x80) != 0);
-
-        $c.a = r as u8;
+        let operand = om::read($c, $b, $addr_mode);
+        om::adc($c, operand);
     }
 });
 
 macro_rules! om_sbc (($c:expr, $b:expr; $addr_mode:expr) => {
     {
-        let value = $c.a as u16;
-        let operand = (om::read($c, $b, $addr_mode) as u16) ^ 0xFF;
-        let c = if $c.p.contains(cpu::CARRY) { 1 } else { 0 };
-        let r = value.wrapping_add(operand).wrapping_add(c);
-
-        $c.p.set(cpu::ZERO, (r & 0xFF) == 0);
-        $c.p.set(cpu::SIGN, r & 0x80 != 0);
-        $c.p.set(cpu::CARRY, ((r >> 8) & 1) != 0);
-        $c.p.set(cpu::OVERFLOW, ((value ^ r) & (operand ^ r) & 0x80) != 0);
-
-        $c.a = r as u8;
+        // Subtraction is addition of the one's complement
+        let operand = om::read($c, $b, $addr_mode);
+        om::adc($c, operand ^ 0xFF);
     }
 });
 
@@ -748,6 +732,172 @@ macro_rules! om_rol_m (($c:expr, $b:expr; $addr_mode:expr) => {
     }
 });
 
+// Unofficial
+// -------------–-------------–-------------–-------------–-------------–-------------–------------
+
+macro_rules! om_lax (($c:expr, $b:expr; $addr_mode:expr) => {
+    {
+        $c.a = om::read($c, $b, $addr_mode);
+        $c.x = $c.a;
+
+        $c.p.set(cpu::ZERO, $c.a == 0);
+        $c.p.set(cpu::SIGN, $c.a & 0x80 != 0);
+    }
+});
+
+macro_rules! om_sax (($c:expr, $b:expr; $addr_mode:expr) => {
+    {
+        let r = $c.a & $c.x;
+        om::write($c, $b, $addr_mode, r);
+    }
+});
+
+macro_rules! om_slo (($c:expr, $b:expr; $addr_mode:expr) => {
+    om::modify($c, $b, $addr_mode, |c, _, operand| {
+        let r = om_asl!(c; operand);
+        c.a |= r;
+
+        c.p.set(cpu::ZERO, c.a == 0);
+        c.p.set(cpu::SIGN, c.a & 0x80 != 0);
+
+        r
+    });
+});
+
+macro_rules! om_rla (($c:expr, $b:expr; $addr_mode:expr) => {
+    om::modify($c, $b, $addr_mode, |c, _, operand| {
+        let r = om_rol!(c; operand);
+        c.a &= r;
+
+        c.p.set(cpu::ZERO, c.a == 0);
+        c.p.set(cpu::SIGN, c.a & 0x80 != 0);
+
+        r
+    });
+});
+
+macro_rules! om_sre (($c:expr, $b:expr; $addr_mode:expr) => {
+    om::modify($c, $b, $addr_mode, |c, _, operand| {
+        let r = om_lsr!(c; operand);
+        c.a ^= r;
+
+        c.p.set(cpu::ZERO, c.a == 0);
+        c.p.set(cpu::SIGN, c.a & 0x80 != 0);
+
+        r
+    });
+});
+
+macro_rules! om_rra (($c:expr, $b:expr; $addr_mode:expr) => {
+    om::modify($c, $b, $addr_mode, |c, _, operand| {
+        let r = om_ror!(c; operand);
+        om::adc(c, r);
+
+        r
+    });
+});
+
+macro_rules! om_dcp (($c:expr, $b:expr; $addr_mode:expr) => {
+    om::modify($c, $b, $addr_mode, |c, _, operand| {
+        let r = operand.wrapping_sub(1);
+
+        c.p.set(cpu::CARRY, c.a >= r);
+        c.p.set(cpu::ZERO, c.a == r);
+        c.p.set(cpu::SIGN, c.a.wrapping_sub(r) & 0x80 != 0);
+
+        r
+    });
+});
+
+macro_rules! om_isc (($c:expr, $b:expr; $addr_mode:expr) => {
+    om::modify($c, $b, $addr_mode, |c, _, operand| {
+        let r = operand.wrapping_add(1);
+        om::adc(c, r ^ 0xFF);
+
+        r
+    });
+});
+
+macro_rules! om_nop (($c:expr, $b:expr; $addr_mode:expr) => {
+    {
+        // Operand is read (and discarded)
+        om::read($c, $b, $addr_mode);
+    }
+});
+
+/// Add with Carry (of an already read operand)
+pub fn adc(c: &mut Context, operand: u8) {
+    let value = c.a as u16;
+    let operand = operand as u16;
+    let carry = if c.p.contains(cpu::CARRY) { 1 } else { 0 };
+    let r = value.wrapping_add(operand).wrapping_add(carry);
+
+    c.p.set(cpu::ZERO, (r & 0xFF) == 0);
+    c.p.set(cpu::SIGN, r & 0x80 != 0);
+    c.p.set(cpu::CARRY, ((r >> 8) & 1) != 0);
+    c.p.set(cpu::OVERFLOW, ((value ^ r) & (operand ^ r) & 0x80) != 0);
+
+    c.a = r as u8;
+}
+
+/// Write for the unstable "SH*" operations (SHY, SHX, TAS, and AHX)
+///
+/// The value written is AND'ed with the high byte of the base address + 1. When
+/// the index crosses a page, that value _also_ replaces the high byte of the effective
+/// address (instead of the carry being added to it).
+pub fn write_high(c: &mut Context, b: &mut Bus, mode: AddressingMode, r: u8) {
+    let (l, h, index) = match mode {
+        AbsoluteX | AbsoluteY => {
+            // Fetch low byte of address; increment PC
+            c.step(b);
+            let l = b.read(c.pc);
+            c.pc = c.pc.wrapping_add(1);
+
+            // Fetch high byte of address; increment PC
+            c.step(b);
+            let h = b.read(c.pc);
+            c.pc = c.pc.wrapping_add(1);
+
+            (l, h, if mode == AbsoluteX { c.x } else { c.y })
+        }
+
+        IndirectIndexed => {
+            // Fetch pointer address; increment PC
+            c.step(b);
+            let ptr = b.read(c.pc);
+            c.pc = c.pc.wrapping_add(1);
+
+            // Fetch low byte of effective address
+            c.step(b);
+            let l = b.read(ptr as u16);
+
+            // Fetch high byte of effective address
+            c.step(b);
+            let h = b.read((ptr.wrapping_add(1)) as u16);
+
+            (l, h, c.y)
+        }
+
+        _ => {
+            panic!("unsupported addressing mode for SH* operation: {:?}", mode);
+        }
+    };
+
+    let overflow_pg = (l as u16 + index as u16) >= 0x100;
+    let l = l.wrapping_add(index);
+
+    // Read from effective address (before the high byte would be fixed)
+    c.step(b);
+    b.read(l as u16 | ((h as u16) << 8));
+
+    let value = r & h.wrapping_add(1);
+    let h = if overflow_pg { value } else { h };
+
+    // Write to effective address
+    c.step(b);
+    b.write(l as u16 | ((h as u16) << 8), value);
+}
+
 pub fn branch(c: &mut Context, b: &mut Bus, flag: Flags, flag_check: bool) {
     // Fetch operand; increment PC
     c.step(b);
@@ -755,6 +905,10 @@ pub fn branch(c: &mut Context, b: &mut Bus, flag: Flags, flag_check: bool) {
     c.pc = c.pc.wrapping_add(1);
 
     if c.p.contains(flag) == flag_check {
+        // A taken branch that does not cross a page does not poll for interrupts
+        // on its last cycle; keep what was polled before it
+        let (nmi_poll, irq_poll) = (c.nmi_poll, c.irq_poll);
+
         // If branch is taken; add operand to PCL
         c.step(b);
         let mut pcl = (c.pc & 0xFF) as u8;
@@ -776,6 +930,9 @@ pub fn branch(c: &mut Context, b: &mut Bus, flag: Flags, flag_check: bool) {
             } else {
                 c.pc = c.pc.wrapping_sub(0x100);
             }
+        } else {
+            c.nmi_poll = nmi_poll;
+            c.irq_poll = irq_poll;
         }
     }
 }
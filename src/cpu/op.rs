@@ -850,30 +850,382 @@ pub fn _00(c: &mut Context, b: &mut Bus) {
     // Increment PC (because it said so)
     c.pc = c.pc.wrapping_add(1);
 
-    // Push PCH on stack; decrement S
-    c.step(b);
-    b.write(0x100 + c.s as u16, (c.pc >> 8) as u8);
-    c.s = c.s.wrapping_sub(1);
+    c.interrupt(b, true);
+}
 
-    // Push PCL on stack; decrement S
-    c.step(b);
-    b.write(0x100 + c.s as u16, c.pc as u8);
-    c.s = c.s.wrapping_sub(1);
+// Unofficial: No Operation [-------]
+// ================================================================================================
 
-    // Push P on stack (with BRK and UNUSED set); decrement S
-    c.step(b);
-    b.write(0x100 + c.s as u16, (c.p | cpu::BREAK).bits | 0x20);
-    c.s = c.s.wrapping_sub(1);
+pub fn _80(c: &mut Context, b: &mut Bus) {
+    om_nop!(c, b; om::Immediate);
+}
 
-    // Fetch PCL
-    c.step(b);
-    let l = b.read(0xFFFE);
+pub fn _04(c: &mut Context, b: &mut Bus) {
+    om_nop!(c, b; om::ZeroPage);
+}
 
-    // Fetch PCH
-    c.step(b);
-    let h = b.read(0xFFFF);
-    c.pc = l as u16 | ((h as u16) << 8);
+pub fn _14(c: &mut Context, b: &mut Bus) {
+    om_nop!(c, b; om::ZeroPageX);
+}
 
-    // Set the IRQ Disable flag
-    c.p.insert(cpu::IRQ_DISABLE);
+pub fn _0C(c: &mut Context, b: &mut Bus) {
+    om_nop!(c, b; om::Absolute);
+}
+
+pub fn _1C(c: &mut Context, b: &mut Bus) {
+    om_nop!(c, b; om::AbsoluteX);
+}
+
+// Unofficial: Load A and X (LAX) [-z----n]
+// ================================================================================================
+
+pub fn _A7(c: &mut Context, b: &mut Bus) {
+    om_lax!(c, b; om::ZeroPage);
+}
+
+pub fn _B7(c: &mut Context, b: &mut Bus) {
+    om_lax!(c, b; om::ZeroPageY);
+}
+
+pub fn _AF(c: &mut Context, b: &mut Bus) {
+    om_lax!(c, b; om::Absolute);
+}
+
+pub fn _BF(c: &mut Context, b: &mut Bus) {
+    om_lax!(c, b; om::AbsoluteY);
+}
+
+pub fn _A3(c: &mut Context, b: &mut Bus) {
+    om_lax!(c, b; om::IndexedIndirect);
+}
+
+pub fn _B3(c: &mut Context, b: &mut Bus) {
+    om_lax!(c, b; om::IndirectIndexed);
+}
+
+// Unofficial: Store A & X (SAX) [-------]
+// ================================================================================================
+
+pub fn _87(c: &mut Context, b: &mut Bus) {
+    om_sax!(c, b; om::ZeroPage);
+}
+
+pub fn _97(c: &mut Context, b: &mut Bus) {
+    om_sax!(c, b; om::ZeroPageY);
+}
+
+pub fn _8F(c: &mut Context, b: &mut Bus) {
+    om_sax!(c, b; om::Absolute);
+}
+
+pub fn _83(c: &mut Context, b: &mut Bus) {
+    om_sax!(c, b; om::IndexedIndirect);
+}
+
+// Unofficial: Shift Left then OR (SLO) [cz----n]
+// ================================================================================================
+
+pub fn _07(c: &mut Context, b: &mut Bus) {
+    om_slo!(c, b; om::ZeroPage);
+}
+
+pub fn _17(c: &mut Context, b: &mut Bus) {
+    om_slo!(c, b; om::ZeroPageX);
+}
+
+pub fn _0F(c: &mut Context, b: &mut Bus) {
+    om_slo!(c, b; om::Absolute);
+}
+
+pub fn _1F(c: &mut Context, b: &mut Bus) {
+    om_slo!(c, b; om::AbsoluteX);
+}
+
+pub fn _1B(c: &mut Context, b: &mut Bus) {
+    om_slo!(c, b; om::AbsoluteY);
+}
+
+pub fn _03(c: &mut Context, b: &mut Bus) {
+    om_slo!(c, b; om::IndexedIndirect);
+}
+
+pub fn _13(c: &mut Context, b: &mut Bus) {
+    om_slo!(c, b; om::IndirectIndexed);
+}
+
+// Unofficial: Rotate Left then AND (RLA) [cz----n]
+// ================================================================================================
+
+pub fn _27(c: &mut Context, b: &mut Bus) {
+    om_rla!(c, b; om::ZeroPage);
+}
+
+pub fn _37(c: &mut Context, b: &mut Bus) {
+    om_rla!(c, b; om::ZeroPageX);
+}
+
+pub fn _2F(c: &mut Context, b: &mut Bus) {
+    om_rla!(c, b; om::Absolute);
+}
+
+pub fn _3F(c: &mut Context, b: &mut Bus) {
+    om_rla!(c, b; om::AbsoluteX);
+}
+
+pub fn _3B(c: &mut Context, b: &mut Bus) {
+    om_rla!(c, b; om::AbsoluteY);
+}
+
+pub fn _23(c: &mut Context, b: &mut Bus) {
+    om_rla!(c, b; om::IndexedIndirect);
+}
+
+pub fn _33(c: &mut Context, b: &mut Bus) {
+    om_rla!(c, b; om::IndirectIndexed);
+}
+
+// Unofficial: Shift Right then EOR (SRE) [cz----n]
+// ================================================================================================
+
+pub fn _47(c: &mut Context, b: &mut Bus) {
+    om_sre!(c, b; om::ZeroPage);
+}
+
+pub fn _57(c: &mut Context, b: &mut Bus) {
+    om_sre!(c, b; om::ZeroPageX);
+}
+
+pub fn _4F(c: &mut Context, b: &mut Bus) {
+    om_sre!(c, b; om::Absolute);
+}
+
+pub fn _5F(c: &mut Context, b: &mut Bus) {
+    om_sre!(c, b; om::AbsoluteX);
+}
+
+pub fn _5B(c: &mut Context, b: &mut Bus) {
+    om_sre!(c, b; om::AbsoluteY);
+}
+
+pub fn _43(c: &mut Context, b: &mut Bus) {
+    om_sre!(c, b; om::IndexedIndirect);
+}
+
+pub fn _53(c: &mut Context, b: &mut Bus) {
+    om_sre!(c, b; om::IndirectIndexed);
+}
+
+// Unofficial: Rotate Right then ADC (RRA) [cz---vn]
+// ================================================================================================
+
+pub fn _67(c: &mut Context, b: &mut Bus) {
+    om_rra!(c, b; om::ZeroPage);
+}
+
+pub fn _77(c: &mut Context, b: &mut Bus) {
+    om_rra!(c, b; om::ZeroPageX);
+}
+
+pub fn _6F(c: &mut Context, b: &mut Bus) {
+    om_rra!(c, b; om::Absolute);
+}
+
+pub fn _7F(c: &mut Context, b: &mut Bus) {
+    om_rra!(c, b; om::AbsoluteX);
+}
+
+pub fn _7B(c: &mut Context, b: &mut Bus) {
+    om_rra!(c, b; om::AbsoluteY);
+}
+
+pub fn _63(c: &mut Context, b: &mut Bus) {
+    om_rra!(c, b; om::IndexedIndirect);
+}
+
+pub fn _73(c: &mut Context, b: &mut Bus) {
+    om_rra!(c, b; om::IndirectIndexed);
+}
+
+// Unofficial: Decrement then Compare (DCP) [cz----n]
+// ================================================================================================
+
+pub fn _C7(c: &mut Context, b: &mut Bus) {
+    om_dcp!(c, b; om::ZeroPage);
+}
+
+pub fn _D7(c: &mut Context, b: &mut Bus) {
+    om_dcp!(c, b; om::ZeroPageX);
+}
+
+pub fn _CF(c: &mut Context, b: &mut Bus) {
+    om_dcp!(c, b; om::Absolute);
+}
+
+pub fn _DF(c: &mut Context, b: &mut Bus) {
+    om_dcp!(c, b; om::AbsoluteX);
+}
+
+pub fn _DB(c: &mut Context, b: &mut Bus) {
+    om_dcp!(c, b; om::AbsoluteY);
+}
+
+pub fn _C3(c: &mut Context, b: &mut Bus) {
+    om_dcp!(c, b; om::IndexedIndirect);
+}
+
+pub fn _D3(c: &mut Context, b: &mut Bus) {
+    om_dcp!(c, b; om::IndirectIndexed);
+}
+
+// Unofficial: Increment then SBC (ISC) [cz---vn]
+// ================================================================================================
+
+pub fn _E7(c: &mut Context, b: &mut Bus) {
+    om_isc!(c, b; om::ZeroPage);
+}
+
+pub fn _F7(c: &mut Context, b: &mut Bus) {
+    om_isc!(c, b; om::ZeroPageX);
+}
+
+pub fn _EF(c: &mut Context, b: &mut Bus) {
+    om_isc!(c, b; om::Absolute);
+}
+
+pub fn _FF(c: &mut Context, b: &mut Bus) {
+    om_isc!(c, b; om::AbsoluteX);
+}
+
+pub fn _FB(c: &mut Context, b: &mut Bus) {
+    om_isc!(c, b; om::AbsoluteY);
+}
+
+pub fn _E3(c: &mut Context, b: &mut Bus) {
+    om_isc!(c, b; om::IndexedIndirect);
+}
+
+pub fn _F3(c: &mut Context, b: &mut Bus) {
+    om_isc!(c, b; om::IndirectIndexed);
+}
+
+// Unofficial: Immediate
+// ================================================================================================
+
+// ANC #nn [cz----n] {2}
+pub fn _0B(c: &mut Context, b: &mut Bus) {
+    om_and!(c, b; om::Immediate);
+
+    // Carry is set from bit 7 (as if by ASL or ROL)
+    let carry = c.p.contains(cpu::SIGN);
+    c.p.set(cpu::CARRY, carry);
+}
+
+// ALR #nn [cz----n] {2}
+pub fn _4B(c: &mut Context, b: &mut Bus) {
+    om_and!(c, b; om::Immediate);
+    om_lsr_a!(c);
+}
+
+// ARR #nn [cz---vn] {2}
+pub fn _6B(c: &mut Context, b: &mut Bus) {
+    om_and!(c, b; om::Immediate);
+    om_ror_a!(c);
+
+    // Carry is set from bit 6 and overflow from bit 6 XOR bit 5
+    let (b6, b5) = (c.a & 0x40 != 0, c.a & 0x20 != 0);
+    c.p.set(cpu::CARRY, b6);
+    c.p.set(cpu::OVERFLOW, b6 != b5);
+}
+
+// AXS #nn [cz----n] {2}
+pub fn _CB(c: &mut Context, b: &mut Bus) {
+    let value = c.a & c.x;
+    let operand = om::read(c, b, om::Immediate);
+    c.x = value.wrapping_sub(operand);
+
+    c.p.set(cpu::CARRY, value >= operand);
+    c.p.set(cpu::ZERO, c.x == 0);
+    c.p.set(cpu::SIGN, c.x & 0x80 != 0);
+}
+
+// SBC #nn [cz---vn] {2}
+pub fn _EB(c: &mut Context, b: &mut Bus) {
+    om_sbc!(c, b; om::Immediate);
+}
+
+// Unofficial: Unstable
+// ================================================================================================
+
+// NOTE: The "magic" constant OR'ed into A by LXA and XAA differs between chips (and even
+//       with temperature); $EE is a common value.
+const MAGIC: u8 = 0xEE;
+
+// LXA #nn [-z----n] {2}
+pub fn _AB(c: &mut Context, b: &mut Bus) {
+    let operand = om::read(c, b, om::Immediate);
+    c.a = (c.a | MAGIC) & operand;
+    c.x = c.a;
+
+    c.p.set(cpu::ZERO, c.a == 0);
+    c.p.set(cpu::SIGN, c.a & 0x80 != 0);
+}
+
+// XAA #nn [-z----n] {2}
+pub fn _8B(c: &mut Context, b: &mut Bus) {
+    let operand = om::read(c, b, om::Immediate);
+    c.a = (c.a | MAGIC) & c.x & operand;
+
+    c.p.set(cpu::ZERO, c.a == 0);
+    c.p.set(cpu::SIGN, c.a & 0x80 != 0);
+}
+
+// LAS nnnn, Y [-z----n] {4*}
+pub fn _BB(c: &mut Context, b: &mut Bus) {
+    let r = om::read(c, b, om::AbsoluteY) & c.s;
+    c.a = r;
+    c.x = r;
+    c.s = r;
+
+    c.p.set(cpu::ZERO, r == 0);
+    c.p.set(cpu::SIGN, r & 0x80 != 0);
+}
+
+// SHY nnnn, X [-------] {5}
+pub fn _9C(c: &mut Context, b: &mut Bus) {
+    let r = c.y;
+    om::write_high(c, b, om::AbsoluteX, r);
+}
+
+// SHX nnnn, Y [-------] {5}
+pub fn _9E(c: &mut Context, b: &mut Bus) {
+    let r = c.x;
+    om::write_high(c, b, om::AbsoluteY, r);
+}
+
+// TAS nnnn, Y [-------] {5}
+pub fn _9B(c: &mut Context, b: &mut Bus) {
+    c.s = c.a & c.x;
+
+    let r = c.s;
+    om::write_high(c, b, om::AbsoluteY, r);
+}
+
+// AHX nnnn, Y [-------] {5}
+pub fn _9F(c: &mut Context, b: &mut Bus) {
+    let r = c.a & c.x;
+    om::write_high(c, b, om::AbsoluteY, r);
+}
+
+// AHX (nn), Y [-------] {6}
+pub fn _93(c: &mut Context, b: &mut Bus) {
+    let r = c.a & c.x;
+    om::write_high(c, b, om::IndirectIndexed, r);
+}
+
+// Unofficial: Jam
+// ================================================================================================
+
+// KIL [-------]
+pub fn _02(c: &mut Context, _: &mut Bus) {
+    c.jam();
 }
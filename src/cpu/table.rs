@@ -280,6 +280,164 @@ impl Default for Table {
 
         operations[0x00] = Operation::new(op::_00, "BRK", 1);
 
+        // Unofficial: No Operation
+        // ========================
+
+        operations[0x1A] = Operation::new(op::_EA, "NOP", 1);
+        operations[0x3A] = Operation::new(op::_EA, "NOP", 1);
+        operations[0x5A] = Operation::new(op::_EA, "NOP", 1);
+        operations[0x7A] = Operation::new(op::_EA, "NOP", 1);
+        operations[0xDA] = Operation::new(op::_EA, "NOP", 1);
+        operations[0xFA] = Operation::new(op::_EA, "NOP", 1);
+
+        operations[0x80] = Operation::new(op::_80, "NOP #${0:X}", 2);
+        operations[0x82] = Operation::new(op::_80, "NOP #${0:X}", 2);
+        operations[0x89] = Operation::new(op::_80, "NOP #${0:X}", 2);
+        operations[0xC2] = Operation::new(op::_80, "NOP #${0:X}", 2);
+        operations[0xE2] = Operation::new(op::_80, "NOP #${0:X}", 2);
+
+        operations[0x04] = Operation::new(op::_04, "NOP ${0:X}", 2);
+        operations[0x44] = Operation::new(op::_04, "NOP ${0:X}", 2);
+        operations[0x64] = Operation::new(op::_04, "NOP ${0:X}", 2);
+
+        operations[0x14] = Operation::new(op::_14, "NOP ${0:X}, X", 2);
+        operations[0x34] = Operation::new(op::_14, "NOP ${0:X}, X", 2);
+        operations[0x54] = Operation::new(op::_14, "NOP ${0:X}, X", 2);
+        operations[0x74] = Operation::new(op::_14, "NOP ${0:X}, X", 2);
+        operations[0xD4] = Operation::new(op::_14, "NOP ${0:X}, X", 2);
+        operations[0xF4] = Operation::new(op::_14, "NOP ${0:X}, X", 2);
+
+        operations[0x0C] = Operation::new(op::_0C, "NOP ${1:X}{0:X}", 3);
+
+        operations[0x1C] = Operation::new(op::_1C, "NOP ${1:X}{0:X}, X", 3);
+        operations[0x3C] = Operation::new(op::_1C, "NOP ${1:X}{0:X}, X", 3);
+        operations[0x5C] = Operation::new(op::_1C, "NOP ${1:X}{0:X}, X", 3);
+        operations[0x7C] = Operation::new(op::_1C, "NOP ${1:X}{0:X}, X", 3);
+        operations[0xDC] = Operation::new(op::_1C, "NOP ${1:X}{0:X}, X", 3);
+        operations[0xFC] = Operation::new(op::_1C, "NOP ${1:X}{0:X}, X", 3);
+
+        // Unofficial: Load A and X
+        // ========================
+
+        operations[0xA7] = Operation::new(op::_A7, "LAX ${0:X}", 2);
+        operations[0xB7] = Operation::new(op::_B7, "LAX ${0:X}, Y", 2);
+        operations[0xAF] = Operation::new(op::_AF, "LAX ${1:X}{0:X}", 3);
+        operations[0xBF] = Operation::new(op::_BF, "LAX ${1:X}{0:X}, Y", 3);
+        operations[0xA3] = Operation::new(op::_A3, "LAX (${0:X}, X)", 2);
+        operations[0xB3] = Operation::new(op::_B3, "LAX (${0:X}), Y", 2);
+
+        // Unofficial: Store A & X
+        // =======================
+
+        operations[0x87] = Operation::new(op::_87, "SAX ${0:X}", 2);
+        operations[0x97] = Operation::new(op::_97, "SAX ${0:X}, Y", 2);
+        operations[0x8F] = Operation::new(op::_8F, "SAX ${1:X}{0:X}", 3);
+        operations[0x83] = Operation::new(op::_83, "SAX (${0:X}, X)", 2);
+
+        // Unofficial: Shift Left then OR
+        // ==============================
+
+        operations[0x07] = Operation::new(op::_07, "SLO ${0:X}", 2);
+        operations[0x17] = Operation::new(op::_17, "SLO ${0:X}, X", 2);
+        operations[0x0F] = Operation::new(op::_0F, "SLO ${1:X}{0:X}", 3);
+        operations[0x1F] = Operation::new(op::_1F, "SLO ${1:X}{0:X}, X", 3);
+        operations[0x1B] = Operation::new(op::_1B, "SLO ${1:X}{0:X}, Y", 3);
+        operations[0x03] = Operation::new(op::_03, "SLO (${0:X}, X)", 2);
+        operations[0x13] = Operation::new(op::_13, "SLO (${0:X}), Y", 2);
+
+        // Unofficial: Rotate Left then AND
+        // ================================
+
+        operations[0x27] = Operation::new(op::_27, "RLA ${0:X}", 2);
+        operations[0x37] = Operation::new(op::_37, "RLA ${0:X}, X", 2);
+        operations[0x2F] = Operation::new(op::_2F, "RLA ${1:X}{0:X}", 3);
+        operations[0x3F] = Operation::new(op::_3F, "RLA ${1:X}{0:X}, X", 3);
+        operations[0x3B] = Operation::new(op::_3B, "RLA ${1:X}{0:X}, Y", 3);
+        operations[0x23] = Operation::new(op::_23, "RLA (${0:X}, X)", 2);
+        operations[0x33] = Operation::new(op::_33, "RLA (${0:X}), Y", 2);
+
+        // Unofficial: Shift Right then EOR
+        // ================================
+
+        operations[0x47] = Operation::new(op::_47, "SRE ${0:X}", 2);
+        operations[0x57] = Operation::new(op::_57, "SRE ${0:X}, X", 2);
+        operations[0x4F] = Operation::new(op::_4F, "SRE ${1:X}{0:X}", 3);
+        operations[0x5F] = Operation::new(op::_5F, "SRE ${1:X}{0:X}, X", 3);
+        operations[0x5B] = Operation::new(op::_5B, "SRE ${1:X}{0:X}, Y", 3);
+        operations[0x43] = Operation::new(op::_43, "SRE (${0:X}, X)", 2);
+        operations[0x53] = Operation::new(op::_53, "SRE (${0:X}), Y", 2);
+
+        // Unofficial: Rotate Right then ADC
+        // =================================
+
+        operations[0x67] = Operation::new(op::_67, "RRA ${0:X}", 2);
+        operations[0x77] = Operation::new(op::_77, "RRA ${0:X}, X", 2);
+        operations[0x6F] = Operation::new(op::_6F, "RRA ${1:X}{0:X}", 3);
+        operations[0x7F] = Operation::new(op::_7F, "RRA ${1:X}{0:X}, X", 3);
+        operations[0x7B] = Operation::new(op::_7B, "RRA ${1:X}{0:X}, Y", 3);
+        operations[0x63] = Operation::new(op::_63, "RRA (${0:X}, X)", 2);
+        operations[0x73] = Operation::new(op::_73, "RRA (${0:X}), Y", 2);
+
+        // Unofficial: Decrement then Compare
+        // ==================================
+
+        operations[0xC7] = Operation::new(op::_C7, "DCP ${0:X}", 2);
+        operations[0xD7] = Operation::new(op::_D7, "DCP ${0:X}, X", 2);
+        operations[0xCF] = Operation::new(op::_CF, "DCP ${1:X}{0:X}", 3);
+        operations[0xDF] = Operation::new(op::_DF, "DCP ${1:X}{0:X}, X", 3);
+        operations[0xDB] = Operation::new(op::_DB, "DCP ${1:X}{0:X}, Y", 3);
+        operations[0xC3] = Operation::new(op::_C3, "DCP (${0:X}, X)", 2);
+        operations[0xD3] = Operation::new(op::_D3, "DCP (${0:X}), Y", 2);
+
+        // Unofficial: Increment then SBC
+        // ==============================
+
+        operations[0xE7] = Operation::new(op::_E7, "ISC ${0:X}", 2);
+        operations[0xF7] = Operation::new(op::_F7, "ISC ${0:X}, X", 2);
+        operations[0xEF] = Operation::new(op::_EF, "ISC ${1:X}{0:X}", 3);
+        operations[0xFF] = Operation::new(op::_FF, "ISC ${1:X}{0:X}, X", 3);
+        operations[0xFB] = Operation::new(op::_FB, "ISC ${1:X}{0:X}, Y", 3);
+        operations[0xE3] = Operation::new(op::_E3, "ISC (${0:X}, X)", 2);
+        operations[0xF3] = Operation::new(op::_F3, "ISC (${0:X}), Y", 2);
+
+        // Unofficial: Immediate
+        // =====================
+
+        operations[0x0B] = Operation::new(op::_0B, "ANC #${0:X}", 2);
+        operations[0x2B] = Operation::new(op::_0B, "ANC #${0:X}", 2);
+        operations[0x4B] = Operation::new(op::_4B, "ALR #${0:X}", 2);
+        operations[0x6B] = Operation::new(op::_6B, "ARR #${0:X}", 2);
+        operations[0xCB] = Operation::new(op::_CB, "AXS #${0:X}", 2);
+        operations[0xEB] = Operation::new(op::_EB, "SBC #${0:X}", 2);
+
+        // Unofficial: Unstable
+        // ====================
+
+        operations[0xAB] = Operation::new(op::_AB, "LXA #${0:X}", 2);
+        operations[0x8B] = Operation::new(op::_8B, "XAA #${0:X}", 2);
+        operations[0xBB] = Operation::new(op::_BB, "LAS ${1:X}{0:X}, Y", 3);
+        operations[0x9C] = Operation::new(op::_9C, "SHY ${1:X}{0:X}, X", 3);
+        operations[0x9E] = Operation::new(op::_9E, "SHX ${1:X}{0:X}, Y", 3);
+        operations[0x9B] = Operation::new(op::_9B, "TAS ${1:X}{0:X}, Y", 3);
+        operations[0x9F] = Operation::new(op::_9F, "AHX ${1:X}{0:X}, Y", 3);
+        operations[0x93] = Operation::new(op::_93, "AHX (${0:X}), Y", 2);
+
+        // Unofficial: Jam
+        // ===============
+
+        operations[0x02] = Operation::new(op::_02, "KIL", 1);
+        operations[0x12] = Operation::new(op::_02, "KIL", 1);
+        operations[0x22] = Operation::new(op::_02, "KIL", 1);
+        operations[0x32] = Operation::new(op::_02, "KIL", 1);
+        operations[0x42] = Operation::new(op::_02, "KIL", 1);
+        operations[0x52] = Operation::new(op::_02, "KIL", 1);
+        operations[0x62] = Operation::new(op::_02, "KIL", 1);
+        operations[0x72] = Operation::new(op::_02, "KIL", 1);
+        operations[0x92] = Operation::new(op::_02, "KIL", 1);
+        operations[0xB2] = Operation::new(op::_02, "KIL", 1);
+        operations[0xD2] = Operation::new(op::_02, "KIL", 1);
+        operations[0xF2] = Operation::new(op::_02, "KIL", 1);
+
         Table { operations: operations }
     }
 }
@@ -0,0 +1,277 @@
+//! Headless runner; runs a ROM for a number of frames without a window (or audio) and
+//! writes the results to disk. Meant for CI and batch jobs.
+//!
+//! ```text
+//! lintel_headless <rom> [--frames N] [--input FILE] [--framebuffer FILE] [--ram FILE]
+//!                       [--trace FILE]
+//! ```
+//!
+//! Input script: one change of the controller state per line; `<frame> <player> <buttons>`
+//! where `player` is 1-4 and `buttons` is a list of buttons joined by `+` (`a`, `b`,
+//! `select`, `start`, `up`, `down`, `left`, `right`) or `-` for none. The state is set
+//! before the frame is run and held until changed. Empty lines and lines starting with `#`
+//! are ignored.
+//!
+//! ```text
+//! # Press start on frame 60 (for 2 frames) then hold right and A
+//! 60 1 start
+//! 62 1 -
+//! 90 1 right+a
+//! ```
+//!
+//! Output: the framebuffer of the last frame (PPM), the internal RAM of the CPU at the end
+//! (2 KiB; raw), and a trace of each instruction that was run.
+
+#[macro_use]
+extern crate log;
+extern crate env_logger;
+
+extern crate lintel_nes;
+
+use lintel_nes::{Machine, Button, PLAYERS, WIDTH, HEIGHT};
+use std::cell::RefCell;
+use std::env;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::process;
+use std::rc::Rc;
+
+/// Number of frames to run (when not given)
+const DEFAULT_FRAMES: u32 = 60;
+
+struct Options {
+    rom: String,
+    frames: u32,
+    input: Option<String>,
+    framebuffer: Option<String>,
+    ram: Option<String>,
+    trace: Option<String>,
+}
+
+/// Change of the controller state of a player (before `frame`)
+struct Change {
+    frame: u32,
+    player: usize,
+    state: u8,
+}
+
+fn usage() -> ! {
+    writeln!(io::stderr(),
+             "usage: lintel_headless <rom> [--frames N] [--input FILE] [--framebuffer FILE] \
+              [--ram FILE] [--trace FILE]")
+        .unwrap();
+
+    process::exit(2);
+}
+
+fn parse_options() -> Options {
+    let mut args = env::args().skip(1);
+    let mut options = Options {
+        rom: String::new(),
+        frames: DEFAULT_FRAMES,
+        input: None,
+        framebuffer: None,
+        ram: None,
+        trace: None,
+    };
+
+    while let Some(arg) = args.next() {
+        match &arg[..] {
+            "--frames" => {
+                options.frames = match args.next().and_then(|value| value.parse().ok()) {
+                    Some(frames) => frames,
+                    None => usage(),
+                };
+            }
+
+            "--input" => options.input = Some(args.next().unwrap_or_else(|| usage())),
+            "--framebuffer" => options.framebuffer = Some(args.next().unwrap_or_else(|| usage())),
+            "--ram" => options.ram = Some(args.next().unwrap_or_else(|| usage())),
+            "--trace" => options.trace = Some(args.next().unwrap_or_else(|| usage())),
+
+            _ if arg.starts_with("--") || !options.rom.is_empty() => usage(),
+
+            _ => options.rom = arg,
+        }
+    }
+
+    if options.rom.is_empty() {
+        usage();
+    }
+
+    options
+}
+
+/// Parse an input script (see the top of the file).
+fn parse_input(filename: &str) -> io::Result<Vec<Change>> {
+    let mut changes = Vec::new();
+
+    for (number, line) in BufReader::new(File::open(filename)?).lines().enumerate() {
+        let line = line?;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        match parse_change(line) {
+            Some(change) => changes.push(change),
+
+            None => {
+                return Err(io::Error::new(io::ErrorKind::InvalidData,
+                                          format!("{}:{}: invalid line \"{}\"",
+                                                  filename,
+                                                  number + 1,
+                                                  line)));
+            }
+        }
+    }
+
+    // Changes are applied in order of frame (and in order of the file within a frame)
+    changes.sort_by_key(|change| change.frame);
+
+    Ok(changes)
+}
+
+fn parse_change(line: &str) -> Option<Change> {
+    let fields: Vec<&str> = line.split_whitespace().collect();
+    if fields.len() != 3 {
+        return None;
+    }
+
+    let frame = match fields[0].parse() {
+        Ok(frame) => frame,
+        Err(_) => return None,
+    };
+
+    let player = match fields[1].parse::<usize>() {
+        Ok(player) if player >= 1 && player <= PLAYERS => player,
+        _ => return None,
+    };
+
+    let mut state = 0;
+    if fields[2] != "-" {
+        for name in fields[2].split('+') {
+            state |= match name {
+                "a" => Button::A,
+                "b" => Button::B,
+                "select" => Button::Select,
+                "start" => Button::Start,
+                "up" => Button::Up,
+                "down" => Button::Down,
+                "left" => Button::Left,
+                "right" => Button::Right,
+
+                _ => return None,
+            } as u8;
+        }
+    }
+
+    Some(Change {
+        frame: frame,
+        player: player - 1,
+        state: state,
+    })
+}
+
+/// Write a frame (BGRA) as a binary PPM image (RGB).
+fn write_framebuffer(filename: &str, data: &[u8]) -> io::Result<()> {
+    let mut file = BufWriter::new(File::create(filename)?);
+    write!(file, "P6\n{} {}\n255\n", WIDTH, HEIGHT)?;
+
+    for pixel in data.chunks(4) {
+        file.write_all(&[pixel[2], pixel[1], pixel[0]])?;
+    }
+
+    Ok(())
+}
+
+fn run(options: &Options) -> io::Result<()> {
+    let changes = match options.input {
+        Some(ref filename) => parse_input(filename)?,
+        None => Vec::new(),
+    };
+
+    let mut trace = match options.trace {
+        Some(ref filename) => Some(BufWriter::new(File::create(filename)?)),
+        None => None,
+    };
+
+    let mut m = Machine::new();
+
+    // Keep the last frame
+    let framebuffer = Rc::new(RefCell::new(vec![0u8; WIDTH * HEIGHT * 4]));
+    {
+        let framebuffer = framebuffer.clone();
+        m.set_on_video_refresh(Box::new(move |frame| {
+            framebuffer.borrow_mut().copy_from_slice(frame.data);
+        }));
+    }
+
+    m.open(&options.rom)
+        .map_err(|error| io::Error::new(error.kind(), format!("{}: {}", options.rom, error)))?;
+    m.reset();
+
+    let mut changes = changes.iter().peekable();
+
+    while m.frame() < options.frames {
+        let frame = m.frame();
+
+        // Apply the changes of input up to this frame
+        while changes.peek().map_or(false, |change| change.frame <= frame) {
+            let change = changes.next().unwrap();
+            m.set_controller_state(change.player, change.state);
+        }
+
+        // Run the frame; an instruction at a time (for the trace)
+        while m.frame() == frame {
+            if let Some(ref mut trace) = trace {
+                let r = m.registers();
+                writeln!(trace,
+                         "{:04X}  A:{:02X} X:{:02X} Y:{:02X} P:{:02X} SP:{:02X} CYC:{} FRAME:{}",
+                         r.pc,
+                         r.a,
+                         r.x,
+                         r.y,
+                         r.p,
+                         r.s,
+                         m.cycles(),
+                         frame)?;
+            }
+
+            let step = m.step_instruction();
+
+            if let Some(ref mut trace) = trace {
+                if step.nmi {
+                    writeln!(trace, "NMI")?;
+                } else if step.irq {
+                    writeln!(trace, "IRQ")?;
+                }
+            }
+        }
+    }
+
+    if let Some(ref filename) = options.framebuffer {
+        write_framebuffer(filename, &framebuffer.borrow())?;
+    }
+
+    if let Some(ref filename) = options.ram {
+        File::create(filename)?.write_all(m.ram())?;
+    }
+
+    info!("ran {} frames ({} cycles)", m.frame(), m.cycles());
+
+    Ok(())
+}
+
+fn main() {
+    // Log: Initialize (level set from environment variables)
+    env_logger::init().unwrap();
+
+    let options = parse_options();
+
+    if let Err(error) = run(&options) {
+        writeln!(io::stderr(), "lintel_headless: {}", error).unwrap();
+
+        process::exit(1);
+    }
+}
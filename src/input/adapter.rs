@@ -0,0 +1,178 @@
+use state::State;
+use super::{Device, Context};
+
+/// NES Four Score (one half; the adapter plugs into both ports)
+///
+/// Reports the controller of the player of the port (1 or 2), then the controller of the
+/// player behind it (3 or 4), then a signature; 0 after that.
+#[derive(Default)]
+pub struct FourScore {
+    /// Port the half is plugged into (0: players 1 and 3; 1: players 2 and 4)
+    port: usize,
+
+    /// Report (24 bits); read from bit 0
+    buffer: u32,
+
+    /// Strobe; when true, the buffer is reloaded on every read
+    strobe: bool,
+}
+
+impl FourScore {
+    pub fn new(port: usize) -> FourScore {
+        FourScore { port: port, ..Default::default() }
+    }
+
+    fn reload(&mut self, ctx: &Context) {
+        // Signature (bits 16-23): $4016: $10; $4017: $20 (in the order read; the first
+        // bit read is the highest, so the buffer holds it reversed: %00001000; %00000100)
+        let signature = if self.port == 0 { 0x08 } else { 0x04 };
+
+        self.buffer = (ctx.player(self.port) as u32) | ((ctx.player(self.port + 2) as u32) << 8) |
+                      (signature << 16);
+    }
+}
+
+impl Device for FourScore {
+    fn reset(&mut self) {
+        self.buffer = 0;
+        self.strobe = false;
+    }
+
+    fn sync(&mut self, s: &mut State) {
+        s.u32(&mut self.buffer);
+        s.bool(&mut self.strobe);
+    }
+
+    fn write(&mut self, value: u8, ctx: &Context) {
+        self.strobe = value & 0x1 != 0;
+
+        if self.strobe {
+            self.reload(ctx);
+        }
+    }
+
+    fn read(&mut self, _: usize, ctx: &Context) -> u8 {
+        if self.strobe {
+            self.reload(ctx);
+        }
+
+        let r = (self.buffer & 0x1) as u8;
+        self.buffer >>= 1;
+
+        r
+    }
+}
+
+/// Famicom (Hori-style) 4-player adapter; attached to the expansion port
+///
+/// Reports the controllers of players 3 ($4016) and 4 ($4017) on D1, each followed by a
+/// signature; 0 after that.
+#[derive(Default)]
+pub struct Hori {
+    /// Report of each port (24 bits); read from bit 0
+    buffer: [u32; 2],
+
+    /// Strobe; when true, the buffers are reloaded on every read
+    strobe: bool,
+}
+
+impl Hori {
+    pub fn new() -> Hori {
+        Default::default()
+    }
+
+    fn reload(&mut self, ctx: &Context) {
+        // Signature (bits 16-23): $4016: $20; $4017: $10 (in the order read; the first
+        // bit read is the highest, so the buffer holds it reversed: %00000100; %00001000)
+        self.buffer = [(ctx.player(2) as u32) | (0x04 << 16),
+                       (ctx.player(3) as u32) | (0x08 << 16)];
+    }
+}
+
+impl Device for Hori {
+    fn reset(&mut self) {
+        self.buffer = [0; 2];
+        self.strobe = false;
+    }
+
+    fn sync(&mut self, s: &mut State) {
+        s.u32(&mut self.buffer[0]);
+        s.u32(&mut self.buffer[1]);
+        s.bool(&mut self.strobe);
+    }
+
+    fn write(&mut self, value: u8, ctx: &Context) {
+        self.strobe = value & 0x1 != 0;
+
+        if self.strobe {
+            self.reload(ctx);
+        }
+    }
+
+    fn read(&mut self, port: usize, ctx: &Context) -> u8 {
+        if self.strobe {
+            self.reload(ctx);
+        }
+
+        let r = (self.buffer[port] & 0x1) as u8;
+        self.buffer[port] >>= 1;
+
+        r << 1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ppu::PPU;
+    use input::{Device, Context, PLAYERS};
+    use super::{FourScore, Hori};
+
+    /// Strobe the device and read 24 bits from `port` (D0, or D1 for the expansion port);
+    /// returns the 3 bytes of the report, each in the order read (first bit read highest)
+    fn report(device: &mut Device, port: usize, bit: u8, players: &[u8; PLAYERS]) -> [u8; 3] {
+        let ppu = PPU::default();
+        let ctx = Context {
+            players: players,
+            ppu: &ppu,
+        };
+
+        device.write(1, &ctx);
+        device.write(0, &ctx);
+
+        let mut r = [0; 3];
+        for byte in r.iter_mut() {
+            for _ in 0..8 {
+                *byte = (*byte << 1) | ((device.read(port, &ctx) >> bit) & 1);
+            }
+        }
+
+        r
+    }
+
+    /// Controller state (A first) as read (first bit read highest)
+    fn reversed(state: u8) -> u8 {
+        (0..8).fold(0, |r, bit| (r << 1) | ((state >> bit) & 1))
+    }
+
+    #[test]
+    fn four_score_report() {
+        let players = [0x01, 0x82, 0x44, 0x18];
+
+        let r = report(&mut FourScore::new(0), 0, 0, &players);
+        assert_eq!(r, [reversed(0x01), reversed(0x44), 0x10]);
+
+        let r = report(&mut FourScore::new(1), 1, 0, &players);
+        assert_eq!(r, [reversed(0x82), reversed(0x18), 0x20]);
+    }
+
+    #[test]
+    fn hori_report() {
+        let players = [0x01, 0x82, 0x44, 0x18];
+
+        let r = report(&mut Hori::new(), 0, 1, &players);
+        assert_eq!(r, [reversed(0x44), 0, 0x20]);
+
+        let r = report(&mut Hori::new(), 1, 1, &players);
+        assert_eq!(r, [reversed(0x18), 0, 0x10]);
+    }
+}
@@ -0,0 +1,308 @@
+use ppu::{PPU, WIDTH, HEIGHT};
+use state::State;
+
+mod standard;
+mod adapter;
+mod zapper;
+mod vaus;
+mod power_pad;
+mod snes_mouse;
+
+pub use self::standard::Standard;
+pub use self::adapter::{FourScore, Hori};
+pub use self::zapper::Zapper;
+pub use self::vaus::Vaus;
+pub use self::power_pad::{PowerPad, POWER_PAD_BUTTONS};
+pub use self::snes_mouse::SnesMouse;
+
+/// Button of a standard controller (the value is the bit of the button in the controller
+/// state)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Button {
+    A = 0x01,
+    B = 0x02,
+    Select = 0x04,
+    Start = 0x08,
+    Up = 0x10,
+    Down = 0x20,
+    Left = 0x40,
+    Right = 0x80,
+}
+
+/// Number of players (with a 4-player adapter)
+pub const PLAYERS: usize = 4;
+
+/// Adapter that connects controllers 3 and 4
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Adapter {
+    /// No adapter; a standard controller in each port (players 1 and 2)
+    None,
+
+    /// NES Four Score; plugs into both ports and reports two controllers on each (players
+    /// 1 and 3 on $4016; players 2 and 4 on $4017) followed by a signature
+    FourScore,
+
+    /// Famicom (Hori-style) 4-player adapter; plugs into the expansion port and reports
+    /// players 3 and 4 on D1 (followed by a signature) next to the hardwired controllers
+    /// of players 1 and 2 on D0
+    Hori,
+}
+
+impl Default for Adapter {
+    fn default() -> Self {
+        Adapter::None
+    }
+}
+
+/// State of the machine that devices can observe when they are written or read
+pub struct Context<'a> {
+    players: &'a [u8; PLAYERS],
+    ppu: &'a PPU,
+}
+
+impl<'a> Context<'a> {
+    /// "Live" state of the standard controller of `player`
+    /// bit:    0     1     2     3     4     5     6     7
+    /// button: A     B  Select Start  Up   Down  Left  Right
+    pub fn player(&self, player: usize) -> u8 {
+        self.players[player]
+    }
+
+    /// Position of the beam of the PPU; (scanline, dot)
+    ///
+    /// NOTE: Scanlines 0-239 are visible and pixel `x` is drawn on dot `x + 1`
+    pub fn beam(&self) -> (u16, u16) {
+        self.ppu.position()
+    }
+
+    /// Color of a pixel in the framebuffer; (red, green, blue)
+    pub fn pixel(&self, x: usize, y: usize) -> (u8, u8, u8) {
+        let offset = (y * WIDTH + x) * 4;
+        let data = &self.ppu.framebuffer;
+
+        if x >= WIDTH || y >= HEIGHT || offset + 2 >= data.len() {
+            return (0, 0, 0);
+        }
+
+        (data[offset + 2], data[offset + 1], data[offset])
+    }
+}
+
+/// Device that can be attached to a controller port (or the expansion port of the Famicom)
+pub trait Device {
+    fn reset(&mut self) {}
+
+    /// Save (or load) the state of the device (its latches and shift registers; not the
+    /// state of host input)
+    fn sync(&mut self, _s: &mut State) {}
+
+    /// Write to $4016 (bits 0-2 are OUT0-OUT2; OUT0 is the strobe of the standard
+    /// controller)
+    fn write(&mut self, value: u8, ctx: &Context);
+
+    /// Read from $4016 (port 0) or $4017 (port 1); returns D0-D4. A device in a controller
+    /// port is only read through its own port; a device in the expansion port is read
+    /// through both.
+    fn read(&mut self, port: usize, ctx: &Context) -> u8;
+
+    /// Set the position of the host pointer (in pixels of the frame; may be outside of it)
+    fn set_pointer(&mut self, _x: i32, _y: i32) {}
+
+    /// Move the host pointer (relative motion; in pixels of the frame)
+    fn move_pointer(&mut self, _dx: i32, _dy: i32) {}
+
+    /// Press (or release) a button of the host pointer (0: primary; 1: secondary)
+    fn set_pointer_button(&mut self, _button: usize, _pressed: bool) {}
+
+    /// Press (or release) a key of the device (for devices with more buttons than a
+    /// standard controller; numbered by the device)
+    fn set_key(&mut self, _key: usize, _pressed: bool) {}
+}
+
+pub struct Input {
+    /// "Live" state of the controller of each player (same structure as `Context::player`)
+    state: [u8; PLAYERS],
+
+    /// Devices attached to each controller port ($4016, $4017)
+    ports: [Box<Device>; 2],
+
+    /// Device attached to the expansion port (Famicom)
+    expansion: Option<Box<Device>>,
+}
+
+impl Default for Input {
+    fn default() -> Input {
+        Input {
+            state: [0; PLAYERS],
+            ports: [Box::new(Standard::new(0)), Box::new(Standard::new(1))],
+            expansion: None,
+        }
+    }
+}
+
+impl Input {
+    /// State of the controller of `player`; `None` (with a warning) if there is no such
+    /// player.
+    fn player(&mut self, player: usize) -> Option<&mut u8> {
+        if player >= PLAYERS {
+            warn!("input: no player {} (players are 0 to {})", player, PLAYERS - 1);
+
+            return None;
+        }
+
+        Some(&mut self.state[player])
+    }
+
+    /// Press a button on the controller of `player` (0 to 3; 2 and 3 need an adapter).
+    pub fn press(&mut self, player: usize, button: Button) {
+        if let Some(state) = self.player(player) {
+            *state |= button as u8;
+        }
+    }
+
+    /// Release a button on the controller of `player` (0 to 3; 2 and 3 need an adapter).
+    pub fn release(&mut self, player: usize, button: Button) {
+        if let Some(state) = self.player(player) {
+            *state &= !(button as u8);
+        }
+    }
+
+    /// Set the state of all buttons on the controller of `player` at once (one bit per
+    /// button; see `Button`).
+    pub fn set_state(&mut self, player: usize, state: u8) {
+        if let Some(player) = self.player(player) {
+            *player = state;
+        }
+    }
+
+    /// State of the controller of each player
+    pub fn states(&self) -> [u8; PLAYERS] {
+        self.state
+    }
+
+    /// Connect standard controllers for all players through a 4-player adapter (or none).
+    pub fn set_adapter(&mut self, adapter: Adapter) {
+        match adapter {
+            Adapter::None => {
+                self.ports = [Box::new(Standard::new(0)), Box::new(Standard::new(1))];
+                self.expansion = None;
+            }
+
+            Adapter::FourScore => {
+                self.ports = [Box::new(FourScore::new(0)), Box::new(FourScore::new(1))];
+                self.expansion = None;
+            }
+
+            Adapter::Hori => {
+                self.ports = [Box::new(Standard::new(0)), Box::new(Standard::new(1))];
+                self.expansion = Some(Box::new(Hori::new()));
+            }
+        }
+    }
+
+    /// Attach a device to a controller port (0: $4016; 1: $4017).
+    pub fn connect(&mut self, port: usize, device: Box<Device>) {
+        if port >= self.ports.len() {
+            warn!("input: no controller port {} (ports are 0 and 1)", port);
+
+            return;
+        }
+
+        self.ports[port] = device;
+    }
+
+    /// Attach a device to the expansion port (or detach with `None`).
+    pub fn connect_expansion(&mut self, device: Option<Box<Device>>) {
+        self.expansion = device;
+    }
+
+    fn devices(&mut self) -> Vec<&mut Box<Device>> {
+        let (port_1, port_2) = self.ports.split_at_mut(1);
+        let mut devices = vec![&mut port_1[0], &mut port_2[0]];
+
+        if let Some(ref mut expansion) = self.expansion {
+            devices.push(expansion);
+        }
+
+        devices
+    }
+
+    pub fn set_pointer(&mut self, x: i32, y: i32) {
+        for device in self.devices() {
+            device.set_pointer(x, y);
+        }
+    }
+
+    pub fn move_pointer(&mut self, dx: i32, dy: i32) {
+        for device in self.devices() {
+            device.move_pointer(dx, dy);
+        }
+    }
+
+    pub fn set_pointer_button(&mut self, button: usize, pressed: bool) {
+        for device in self.devices() {
+            device.set_pointer_button(button, pressed);
+        }
+    }
+
+    pub fn set_key(&mut self, key: usize, pressed: bool) {
+        for device in self.devices() {
+            device.set_key(key, pressed);
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.state = [0; PLAYERS];
+
+        for device in self.devices() {
+            device.reset();
+        }
+    }
+
+    /// Save (or load) the state of the attached devices; the devices must be the same
+    /// when loading as they were when saving.
+    pub fn sync(&mut self, s: &mut State) {
+        for device in self.devices() {
+            device.sync(s);
+        }
+    }
+
+    pub fn read(&mut self, address: u16, ppu: &PPU) -> u8 {
+        let ctx = Context {
+            players: &self.state,
+            ppu: ppu,
+        };
+
+        let port = match address {
+            0x4016 => 0,
+            0x4017 => 1,
+
+            _ => unreachable!(),
+        };
+
+        let mut r = self.ports[port].read(port, &ctx);
+
+        if let Some(ref mut expansion) = self.expansion {
+            r |= expansion.read(port, &ctx);
+        }
+
+        r
+    }
+
+    pub fn write(&mut self, address: u16, value: u8, ppu: &PPU) {
+        if address == 0x4016 {
+            let ctx = Context {
+                players: &self.state,
+                ppu: ppu,
+            };
+
+            for device in self.ports.iter_mut() {
+                device.write(value, &ctx);
+            }
+
+            if let Some(ref mut expansion) = self.expansion {
+                expansion.write(value, &ctx);
+            }
+        }
+    }
+}
@@ -0,0 +1,194 @@
+use state::State;
+use super::{Device, Context};
+
+/// Number of buttons on the mat
+pub const POWER_PAD_BUTTONS: usize = 12;
+
+/// Power Pad (NES) and Family Trainer (Famicom); a mat of 12 buttons (numbered 1-12 in
+/// rows of 4 from the top left; side B)
+///
+/// NES (controller port): serial data on D3 (buttons 2, 1, 5, 9, 6, 10, 11, 7) and
+/// D4 (buttons 4, 3, 12, 8; then 1); 1 is pressed.
+///
+/// Famicom (expansion port): a row is selected by clearing one of OUT0-OUT2 ($4016); D1-D4
+/// of $4017 then report buttons 4, 3, 2, 1 (OUT2), 8, 7, 6, 5 (OUT1), or 12, 11, 10, 9
+/// (OUT0); 0 is pressed.
+///
+/// The buttons are pressed with the keys of the device (0-11 for buttons 1-12).
+#[derive(Default)]
+pub struct PowerPad {
+    /// Famicom variant (Family Trainer; attached to the expansion port)
+    famicom: bool,
+
+    /// State of the buttons (bit 0: button 1)
+    state: u16,
+
+    /// Shift registers (D3, D4); read from bit 0
+    buffer: [u8; 2],
+
+    /// Strobe (NES); when true, the shift registers are reloaded on every read
+    strobe: bool,
+
+    /// Row select (Famicom; OUT0-OUT2 as last written)
+    select: u8,
+}
+
+impl PowerPad {
+    /// Power Pad (attach to a controller port; usually the second)
+    pub fn new() -> PowerPad {
+        PowerPad { select: 0x07, ..Default::default() }
+    }
+
+    /// Family Trainer (attach to the expansion port)
+    pub fn family_trainer() -> PowerPad {
+        PowerPad { famicom: true, ..PowerPad::new() }
+    }
+
+    fn pressed(&self, button: usize) -> u8 {
+        ((self.state >> (button - 1)) & 1) as u8
+    }
+
+    /// Bits from the buttons (bit 0 from the first button)
+    fn bits(&self, buttons: &[usize]) -> u8 {
+        buttons.iter().enumerate().fold(0, |r, (i, &button)| r | (self.pressed(button) << i))
+    }
+
+    fn reload(&mut self) {
+        self.buffer = [self.bits(&[2, 1, 5, 9, 6, 10, 11, 7]), self.bits(&[4, 3, 12, 8]) | 0xF0];
+    }
+}
+
+impl Device for PowerPad {
+    fn reset(&mut self) {
+        self.buffer = [0; 2];
+        self.strobe = false;
+        self.select = 0x07;
+    }
+
+    fn sync(&mut self, s: &mut State) {
+        s.slice(&mut self.buffer);
+        s.bool(&mut self.strobe);
+        s.u8(&mut self.select);
+    }
+
+    fn write(&mut self, value: u8, _: &Context) {
+        self.strobe = value & 0x1 != 0;
+        self.select = value & 0x07;
+
+        if self.strobe {
+            self.reload();
+        }
+    }
+
+    fn read(&mut self, port: usize, _: &Context) -> u8 {
+        if self.famicom {
+            if port == 0 {
+                return 0;
+            }
+
+            let row = if self.select & 0x04 == 0 {
+                self.bits(&[4, 3, 2, 1])
+            } else if self.select & 0x02 == 0 {
+                self.bits(&[8, 7, 6, 5])
+            } else if self.select & 0x01 == 0 {
+                self.bits(&[12, 11, 10, 9])
+            } else {
+                0
+            };
+
+            return (!row & 0x0F) << 1;
+        }
+
+        if self.strobe {
+            self.reload();
+        }
+
+        let r = ((self.buffer[0] & 1) << 3) | ((self.buffer[1] & 1) << 4);
+
+        // Both registers report 1 once they are empty
+        self.buffer[0] = (self.buffer[0] >> 1) | 0x80;
+        self.buffer[1] = (self.buffer[1] >> 1) | 0x80;
+
+        r
+    }
+
+    fn set_key(&mut self, key: usize, pressed: bool) {
+        if key < POWER_PAD_BUTTONS {
+            if pressed {
+                self.state |= 1 << key;
+            } else {
+                self.state &= !(1 << key);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ppu::PPU;
+    use input::{Device, Context, PLAYERS};
+    use super::PowerPad;
+
+    /// Write each of `values` to $4016 and read `count` times from `port`
+    fn read(pad: &mut PowerPad, values: &[u8], port: usize, count: usize) -> Vec<u8> {
+        let ppu = PPU::default();
+        let players = [0; PLAYERS];
+        let ctx = Context {
+            players: &players,
+            ppu: &ppu,
+        };
+
+        for &value in values {
+            pad.write(value, &ctx);
+        }
+
+        (0..count).map(|_| pad.read(port, &ctx)).collect()
+    }
+
+    /// Power Pad (or Family Trainer) with buttons 1, 7, and 12 pressed
+    fn pad(mut pad: PowerPad) -> PowerPad {
+        for &key in &[0, 6, 11] {
+            pad.set_key(key, true);
+        }
+
+        pad
+    }
+
+    #[test]
+    fn power_pad() {
+        // D3: 2, 1, 5, 9, 6, 10, 11, 7; D4: 4, 3, 12, 8; then 1 on both
+        let r = read(&mut pad(PowerPad::new()), &[1, 0], 1, 10);
+        let d3: Vec<u8> = r.iter().map(|&r| (r >> 3) & 1).collect();
+        let d4: Vec<u8> = r.iter().map(|&r| (r >> 4) & 1).collect();
+
+        assert_eq!(d3, [0, 1, 0, 0, 0, 0, 0, 1, 1, 1]);
+        assert_eq!(d4, [0, 0, 1, 0, 1, 1, 1, 1, 1, 1]);
+    }
+
+    #[test]
+    fn release() {
+        let mut pad = pad(PowerPad::new());
+        for key in 0..12 {
+            pad.set_key(key, false);
+        }
+
+        let r = read(&mut pad, &[1, 0], 1, 4);
+        assert_eq!(r, [0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn family_trainer() {
+        // Rows 4-1 (OUT2 clear), 8-5 (OUT1 clear), and 12-9 (OUT0 clear) on D1-D4; 0 is
+        // pressed
+        let mut pad = pad(PowerPad::family_trainer());
+        assert_eq!(read(&mut pad, &[0x03], 1, 1), [0x0E]);
+        assert_eq!(read(&mut pad, &[0x05], 1, 1), [0x1A]);
+        assert_eq!(read(&mut pad, &[0x06], 1, 1), [0x1C]);
+
+        // No row selected; nothing is pressed
+        assert_eq!(read(&mut pad, &[0x07], 1, 1), [0x1E]);
+
+        // Nothing on $4016
+        assert_eq!(read(&mut pad, &[0x03], 0, 1), [0x00]);
+    }
+}
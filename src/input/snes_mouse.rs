@@ -0,0 +1,185 @@
+use state::State;
+use super::{Device, Context};
+
+/// SNES mouse (as used by homebrew on the NES; controller port)
+///
+/// Reports 32 bits on D0 (each byte from bit 7; then 1):
+///
+/// 1. %00000000
+/// 2. right button, left button, sensitivity (2 bits), and the signature %0001
+/// 3. vertical movement (bit 7: direction (1: up); bits 0-6: magnitude)
+/// 4. horizontal movement (bit 7: direction (1: left); bits 0-6: magnitude)
+///
+/// Movement is accumulated from the relative motion of the host pointer between reports
+/// and the buttons are the buttons of the host pointer. Reading while the strobe is high
+/// cycles the sensitivity.
+#[derive(Default)]
+pub struct SnesMouse {
+    /// Buttons (left, right)
+    buttons: [bool; 2],
+
+    /// Movement since the last report
+    dx: i32,
+    dy: i32,
+
+    /// Sensitivity (0-2)
+    sensitivity: u8,
+
+    /// Report; read from bit 31
+    buffer: u32,
+
+    /// Number of bits read since the report was latched
+    count: u8,
+
+    /// Strobe
+    strobe: bool,
+}
+
+impl SnesMouse {
+    pub fn new() -> SnesMouse {
+        Default::default()
+    }
+
+    /// Movement as sign and magnitude (7 bits; clamped)
+    fn movement(delta: i32) -> u32 {
+        let magnitude = if delta.abs() > 0x7F { 0x7F } else { delta.abs() as u32 };
+
+        if delta < 0 { 0x80 | magnitude } else { magnitude }
+    }
+
+    fn latch(&mut self) {
+        let status = ((self.buttons[1] as u32) << 7) | ((self.buttons[0] as u32) << 6) |
+                     ((self.sensitivity as u32) << 4) | 0x01;
+
+        self.buffer = (status << 16) | (SnesMouse::movement(self.dy) << 8) |
+                      SnesMouse::movement(self.dx);
+
+        self.count = 0;
+        self.dx = 0;
+        self.dy = 0;
+    }
+}
+
+impl Device for SnesMouse {
+    fn reset(&mut self) {
+        self.buffer = 0;
+        self.count = 0;
+        self.strobe = false;
+        self.sensitivity = 0;
+    }
+
+    fn sync(&mut self, s: &mut State) {
+        s.u8(&mut self.sensitivity);
+        s.u32(&mut self.buffer);
+        s.u8(&mut self.count);
+        s.bool(&mut self.strobe);
+    }
+
+    fn write(&mut self, value: u8, _: &Context) {
+        let strobe = value & 0x1 != 0;
+
+        // The report is latched as the strobe goes low
+        if self.strobe && !strobe {
+            self.latch();
+        }
+
+        self.strobe = strobe;
+    }
+
+    fn read(&mut self, _: usize, _: &Context) -> u8 {
+        if self.strobe {
+            self.sensitivity = (self.sensitivity + 1) % 3;
+
+            return 0;
+        }
+
+        if self.count >= 32 {
+            return 1;
+        }
+
+        let r = (self.buffer >> 31) as u8;
+        self.buffer <<= 1;
+        self.count += 1;
+
+        r
+    }
+
+    fn move_pointer(&mut self, dx: i32, dy: i32) {
+        self.dx += dx;
+        self.dy += dy;
+    }
+
+    fn set_pointer_button(&mut self, button: usize, pressed: bool) {
+        if button < 2 {
+            self.buttons[button] = pressed;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ppu::PPU;
+    use input::{Device, Context, PLAYERS};
+    use super::SnesMouse;
+
+    /// Strobe the mouse (reading `cycles` times while the strobe is high) and read the
+    /// report; 4 bytes (each from bit 7) and the bit after them
+    fn report(mouse: &mut SnesMouse, cycles: usize) -> ([u8; 4], u8) {
+        let ppu = PPU::default();
+        let players = [0; PLAYERS];
+        let ctx = Context {
+            players: &players,
+            ppu: &ppu,
+        };
+
+        mouse.write(1, &ctx);
+        for _ in 0..cycles {
+            mouse.read(0, &ctx);
+        }
+
+        mouse.write(0, &ctx);
+
+        let mut r = [0; 4];
+        for byte in r.iter_mut() {
+            for _ in 0..8 {
+                *byte = (*byte << 1) | (mouse.read(0, &ctx) & 1);
+            }
+        }
+
+        (r, mouse.read(0, &ctx))
+    }
+
+    #[test]
+    fn report_format() {
+        let mut mouse = SnesMouse::new();
+        mouse.move_pointer(3, 1);
+        mouse.move_pointer(2, -4);
+        mouse.set_pointer_button(0, true);
+
+        assert_eq!(report(&mut mouse, 0), ([0x00, 0x41, 0x83, 0x05], 1));
+
+        // Movement is reset by each report
+        mouse.set_pointer_button(0, false);
+        mouse.set_pointer_button(1, true);
+        assert_eq!(report(&mut mouse, 0), ([0x00, 0x81, 0x00, 0x00], 1));
+    }
+
+    #[test]
+    fn movement_clamped() {
+        let mut mouse = SnesMouse::new();
+        mouse.move_pointer(-200, 500);
+
+        assert_eq!(report(&mut mouse, 0).0, [0x00, 0x01, 0x7F, 0xFF]);
+    }
+
+    #[test]
+    fn sensitivity() {
+        // Each read while the strobe is high cycles the sensitivity (0, 1, 2)
+        let mut mouse = SnesMouse::new();
+        assert_eq!(report(&mut mouse, 1).0[1], 0x11);
+        assert_eq!(report(&mut mouse, 1).0[1], 0x21);
+        assert_eq!(report(&mut mouse, 1).0[1], 0x01);
+        assert_eq!(report(&mut mouse, 0).0[1], 0x01);
+        assert_eq!(report(&mut mouse, 5).0[1], 0x21);
+    }
+}
@@ -0,0 +1,131 @@
+use state::State;
+use super::{Device, Context};
+
+/// Standard controller
+#[derive(Default)]
+pub struct Standard {
+    /// Player whose state is reported
+    player: usize,
+
+    /// Buffer (same structure as the state); read from bit 0 and filled with 1 as it
+    /// shifts (after 8 reads an official controller reports 1)
+    buffer: u8,
+
+    /// Number of bits read since the last reload
+    count: u8,
+
+    /// Strobe; when true, the buffer is reloaded on every read (and reports button A)
+    strobe: bool,
+}
+
+impl Standard {
+    pub fn new(player: usize) -> Standard {
+        Standard { player: player, ..Default::default() }
+    }
+
+    fn reload(&mut self, ctx: &Context) {
+        self.buffer = ctx.player(self.player);
+        self.count = 0;
+    }
+}
+
+impl Device for Standard {
+    fn reset(&mut self) {
+        self.buffer = 0;
+        self.count = 0;
+        self.strobe = false;
+    }
+
+    fn sync(&mut self, s: &mut State) {
+        s.u8(&mut self.buffer);
+        s.u8(&mut self.count);
+        s.bool(&mut self.strobe);
+    }
+
+    fn write(&mut self, value: u8, ctx: &Context) {
+        self.strobe = value & 0x1 != 0;
+
+        if self.strobe {
+            self.reload(ctx);
+        }
+    }
+
+    fn read(&mut self, _: usize, ctx: &Context) -> u8 {
+        if self.strobe {
+            self.reload(ctx);
+        }
+
+        if self.count >= 8 {
+            return 1;
+        }
+
+        let r = self.buffer & 0x1;
+        self.buffer >>= 1;
+        self.count += 1;
+
+        r
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ppu::PPU;
+    use input::{Device, Context};
+    use super::Standard;
+
+    #[test]
+    fn report() {
+        let ppu = PPU::default();
+        let players = [0x00, 0x95, 0x00, 0x00];
+        let ctx = Context {
+            players: &players,
+            ppu: &ppu,
+        };
+
+        // A, B, Select, Start, Up, Down, Left, Right; then 1 (from an official controller)
+
+        let mut standard = Standard::new(1);
+        standard.write(1, &ctx);
+        standard.write(0, &ctx);
+
+        let bits: Vec<u8> = (0..12).map(|_| standard.read(1, &ctx)).collect();
+        assert_eq!(bits, [1, 0, 1, 0, 1, 0, 0, 1, 1, 1, 1, 1]);
+    }
+
+    #[test]
+    fn strobe() {
+        let ppu = PPU::default();
+        let mut players = [0x01, 0x00, 0x00, 0x00];
+        let mut standard = Standard::new(0);
+
+        // While the strobe is high, each read reports the live state of A
+        for &a in &[1, 1, 0, 1] {
+            players[0] = a;
+            let ctx = Context {
+                players: &players,
+                ppu: &ppu,
+            };
+
+            standard.write(1, &ctx);
+            assert_eq!(standard.read(0, &ctx), a);
+            assert_eq!(standard.read(0, &ctx), a);
+        }
+
+        // The state is latched as of the last write (or read) with the strobe high
+        players[0] = 0x02;
+        let ctx = Context {
+            players: &players,
+            ppu: &ppu,
+        };
+        standard.write(1, &ctx);
+        standard.write(0, &ctx);
+
+        players[0] = 0x01;
+        let ctx = Context {
+            players: &players,
+            ppu: &ppu,
+        };
+        assert_eq!(standard.read(0, &ctx), 0);
+        assert_eq!(standard.read(0, &ctx), 1);
+    }
+}
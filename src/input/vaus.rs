@@ -0,0 +1,160 @@
+use state::State;
+use super::{Device, Context};
+
+/// Position of the knob (potentiometer) at each end of its range
+const MIN: i32 = 98;
+const MAX: i32 = 242;
+
+/// Arkanoid controller (Vaus); a paddle with a fire button
+///
+/// NES (controller port; $4017):
+///
+/// 43210
+/// |+---- fire button (1: pressed)
+/// +----- serial data of the position of the knob (inverted; bit 7 first)
+///
+/// Famicom (expansion port): fire button on D1 of $4016; serial data of the position on D1
+/// of $4017 (inverted; bit 7 first).
+///
+/// The knob follows the horizontal position of the host pointer and fire is its primary
+/// button.
+#[derive(Default)]
+pub struct Vaus {
+    /// Famicom variant (attached to the expansion port)
+    famicom: bool,
+
+    /// Position of the knob
+    position: u8,
+
+    /// Fire button
+    fire: bool,
+
+    /// Shift register (latched from the position on strobe); read from bit 7
+    buffer: u8,
+
+    /// Strobe; when true, the shift register is reloaded on every read
+    strobe: bool,
+}
+
+impl Vaus {
+    /// NES variant (attach to a controller port; usually the second)
+    pub fn new() -> Vaus {
+        Vaus { position: MIN as u8, ..Default::default() }
+    }
+
+    /// Famicom variant (attach to the expansion port)
+    pub fn famicom() -> Vaus {
+        Vaus { famicom: true, ..Vaus::new() }
+    }
+}
+
+impl Device for Vaus {
+    fn reset(&mut self) {
+        self.buffer = 0;
+        self.strobe = false;
+    }
+
+    fn sync(&mut self, s: &mut State) {
+        s.u8(&mut self.buffer);
+        s.bool(&mut self.strobe);
+    }
+
+    fn write(&mut self, value: u8, _: &Context) {
+        self.strobe = value & 0x1 != 0;
+
+        if self.strobe {
+            self.buffer = self.position;
+        }
+    }
+
+    fn read(&mut self, port: usize, _: &Context) -> u8 {
+        if self.strobe {
+            self.buffer = self.position;
+        }
+
+        let fire = self.fire as u8;
+
+        if self.famicom && port == 0 {
+            return fire << 1;
+        }
+
+        let data = (!self.buffer >> 7) & 1;
+        self.buffer <<= 1;
+
+        if self.famicom {
+            data << 1
+        } else {
+            (data << 4) | (fire << 3)
+        }
+    }
+
+    fn set_pointer(&mut self, x: i32, _: i32) {
+        let x = if x < 0 { 0 } else if x > 255 { 255 } else { x };
+
+        self.position = (MIN + x * (MAX - MIN) / 255) as u8;
+    }
+
+    fn set_pointer_button(&mut self, button: usize, pressed: bool) {
+        if button == 0 {
+            self.fire = pressed;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ppu::PPU;
+    use input::{Device, Context, PLAYERS};
+    use super::Vaus;
+
+    /// Strobe the device and read `count` times from `port`
+    fn read(vaus: &mut Vaus, port: usize, count: usize) -> Vec<u8> {
+        let ppu = PPU::default();
+        let players = [0; PLAYERS];
+        let ctx = Context {
+            players: &players,
+            ppu: &ppu,
+        };
+
+        vaus.write(1, &ctx);
+        vaus.write(0, &ctx);
+
+        (0..count).map(|_| vaus.read(port, &ctx)).collect()
+    }
+
+    #[test]
+    fn position() {
+        // The knob spans 98 to 242; reported inverted (bit 7 first) on D4
+        let cases = [(0, [1, 0, 0, 1, 1, 1, 0, 1]),
+                     (-10, [1, 0, 0, 1, 1, 1, 0, 1]),
+                     (255, [0, 0, 0, 0, 1, 1, 0, 1]),
+                     (300, [0, 0, 0, 0, 1, 1, 0, 1])];
+        for &(x, expected) in &cases {
+            let mut vaus = Vaus::new();
+            vaus.set_pointer(x, 0);
+
+            let bits: Vec<u8> = read(&mut vaus, 1, 8).iter().map(|&r| r >> 4).collect();
+            assert_eq!(bits, expected, "x = {}", x);
+        }
+    }
+
+    #[test]
+    fn fire() {
+        let mut vaus = Vaus::new();
+        assert!(read(&mut vaus, 1, 8).iter().all(|&r| r & 0x08 == 0));
+
+        vaus.set_pointer_button(0, true);
+        assert!(read(&mut vaus, 1, 8).iter().all(|&r| r & 0x08 != 0));
+    }
+
+    #[test]
+    fn famicom() {
+        // Fire on D1 of $4016; the position on D1 of $4017
+        let mut vaus = Vaus::famicom();
+        vaus.set_pointer(255, 0);
+        vaus.set_pointer_button(0, true);
+
+        assert_eq!(read(&mut vaus, 0, 4), [0x02, 0x02, 0x02, 0x02]);
+        assert_eq!(read(&mut vaus, 1, 8), [0, 0, 0, 0, 2, 2, 0, 2]);
+    }
+}
@@ -0,0 +1,179 @@
+use super::{Device, Context};
+
+/// Distance (in pixels) from the aim that the photodiode sees
+const RADIUS: i32 = 3;
+
+/// Number of scanlines that the photodiode stays lit after the beam draws a bright pixel
+const LIT_LINES: i32 = 20;
+
+/// Brightness (average of the color components) at which a pixel lights the photodiode
+const BRIGHTNESS_THRESHOLD: u16 = 0x55;
+
+/// Zapper (light gun)
+///
+/// 43210
+/// |+---- light sense (0: detected; 1: not detected)
+/// +----- trigger (0: released; 1: pulled)
+///
+/// Aimed with the host pointer and fired with its primary button.
+#[derive(Default)]
+pub struct Zapper {
+    /// Aim (in pixels of the frame)
+    x: i32,
+    y: i32,
+
+    /// Trigger
+    trigger: bool,
+}
+
+impl Zapper {
+    pub fn new() -> Zapper {
+        Default::default()
+    }
+
+    /// The photodiode is lit by bright pixels near the aim that the beam drew recently
+    fn light(&self, ctx: &Context) -> bool {
+        let (line, dot) = ctx.beam();
+        let (line, dot) = (line as i32, dot as i32);
+
+        for y in (self.y - RADIUS)..(self.y + RADIUS + 1) {
+            // Only scanlines at or above the beam (from this frame) within the last
+            // few scanlines
+            if y < 0 || y > line || line - y > LIT_LINES {
+                continue;
+            }
+
+            for x in (self.x - RADIUS)..(self.x + RADIUS + 1) {
+                // Pixel `x` is drawn on dot `x + 1`
+                if x < 0 || (y == line && x + 1 > dot) {
+                    continue;
+                }
+
+                let (r, g, b) = ctx.pixel(x as usize, y as usize);
+                if (r as u16 + g as u16 + b as u16) / 3 >= BRIGHTNESS_THRESHOLD {
+                    return true;
+                }
+            }
+        }
+
+        false
+    }
+}
+
+impl Device for Zapper {
+    fn write(&mut self, _: u8, _: &Context) {}
+
+    fn read(&mut self, _: usize, ctx: &Context) -> u8 {
+        let light = if self.light(ctx) { 0 } else { 0x08 };
+        let trigger = if self.trigger { 0x10 } else { 0 };
+
+        light | trigger
+    }
+
+    fn set_pointer(&mut self, x: i32, y: i32) {
+        self.x = x;
+        self.y = y;
+    }
+
+    fn set_pointer_button(&mut self, button: usize, pressed: bool) {
+        if button == 0 {
+            self.trigger = pressed;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ppu::{self, PPU, WIDTH};
+    use input::{Device, Context, PLAYERS};
+    use super::Zapper;
+
+    struct Memory;
+
+    impl ppu::Controller for Memory {
+        fn try_read(&mut self, _: u16, ptr: &mut u8) -> bool {
+            *ptr = 0;
+            true
+        }
+
+        fn try_write(&mut self, _: u16, _: u8) -> bool {
+            true
+        }
+    }
+
+    /// PPU with the beam at `dot` of `line` (rendering is disabled so the frame is black)
+    /// and a white pixel at (`x`, `y`)
+    fn ppu(line: u16, dot: u16, x: usize, y: usize) -> PPU {
+        let mut ppu = PPU::default();
+        ppu.reset();
+
+        let mut nmi_occurred = false;
+        while ppu.position() != (line, dot) {
+            ppu.step(&mut Memory, &mut nmi_occurred);
+        }
+
+        let offset = (y * WIDTH + x) * 4;
+        for value in ppu.framebuffer[offset..offset + 3].iter_mut() {
+            *value = 0xFF;
+        }
+
+        ppu
+    }
+
+    /// Light sense bit (bit 3 clear when light is detected) of a Zapper aimed at (100, 50)
+    fn light(ppu: &PPU) -> bool {
+        let players = [0; PLAYERS];
+        let ctx = Context {
+            players: &players,
+            ppu: ppu,
+        };
+
+        let mut zapper = Zapper::new();
+        zapper.set_pointer(100, 50);
+
+        zapper.read(1, &ctx) & 0x08 == 0
+    }
+
+    #[test]
+    fn trigger() {
+        let ppu = ppu(0, 0, 0, 0);
+        let players = [0; PLAYERS];
+        let ctx = Context {
+            players: &players,
+            ppu: &ppu,
+        };
+
+        let mut zapper = Zapper::new();
+        assert_eq!(zapper.read(1, &ctx), 0x08);
+
+        zapper.set_pointer_button(0, true);
+        assert_eq!(zapper.read(1, &ctx), 0x18);
+
+        // Only the primary button pulls the trigger
+        zapper.set_pointer_button(0, false);
+        zapper.set_pointer_button(1, true);
+        assert_eq!(zapper.read(1, &ctx), 0x08);
+    }
+
+    #[test]
+    fn light_near_aim() {
+        assert!(light(&ppu(60, 0, 100, 50)));
+        assert!(light(&ppu(60, 0, 103, 47)));
+        assert!(!light(&ppu(60, 0, 104, 50)));
+        assert!(!light(&ppu(60, 0, 100, 54)));
+    }
+
+    #[test]
+    fn light_follows_beam() {
+        // Not yet drawn (pixel `x` is drawn on dot `x + 1`)
+        assert!(!light(&ppu(50, 100, 100, 50)));
+        assert!(light(&ppu(50, 101, 100, 50)));
+
+        // Lit for 20 scanlines
+        assert!(light(&ppu(70, 0, 100, 50)));
+        assert!(!light(&ppu(71, 0, 100, 50)));
+
+        // Not lit by a pixel below the beam (from the last frame)
+        assert!(!light(&ppu(10, 0, 100, 50)));
+    }
+}
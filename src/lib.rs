@@ -0,0 +1,95 @@
+//! Lintel; an NES emulator.
+//!
+//! The core is driven through [`Machine`](struct.Machine.html), a frame at a time with
+//! [`Machine::run_frame`](struct.Machine.html#method.run_frame) (or an instruction at a
+//! time). Output is returned by `run_frame` (a [`Frame`](struct.Frame.html) of video and
+//! the [`Samples`](struct.Samples.html) of audio produced during it) and also delivered
+//! through callbacks so that any frontend can present it. Input is set per player with
+//! [`Machine::press_button`](struct.Machine.html#method.press_button) and
+//! [`Machine::release_button`](struct.Machine.html#method.release_button) (or a whole
+//! controller at once with
+//! [`Machine::set_controller_state`](struct.Machine.html#method.set_controller_state)).
+//!
+//! The SDL2 frontend (the `lintel_nes` binary) is behind the `sdl` feature (on by default);
+//! build with `--no-default-features` for just the core.
+//!
+//! ```no_run
+//! extern crate lintel_nes;
+//!
+//! let mut m = lintel_nes::Machine::new();
+//!
+//! m.open("game.nes").unwrap();
+//! m.reset();
+//!
+//! loop {
+//!     let output = m.run_frame();
+//!
+//!     // Present `output.video.data` (`output.video.width` x `output.video.height`;
+//!     // ARGB8888) and play `output.audio.data`
+//! }
+//! ```
+//!
+//! # Stability
+//!
+//! The crate is pre-1.0; a breaking change bumps the minor version (0.1 to 0.2). Within a
+//! minor version the items re-exported here are supported:
+//!
+//! - Driving the machine: [`Machine`](struct.Machine.html), [`Step`](struct.Step.html),
+//!   [`FrameStep`](struct.FrameStep.html), [`Frame`](struct.Frame.html),
+//!   [`Samples`](struct.Samples.html), [`Registers`](struct.Registers.html),
+//!   [`JamBehavior`](enum.JamBehavior.html), and the constants (`WIDTH`, `HEIGHT`,
+//!   `FRAME_RATE`, `SAMPLE_RATE`, `PLAYERS`, `POWER_PAD_BUTTONS`).
+//! - Input: [`Button`](enum.Button.html), [`Adapter`](enum.Adapter.html), the bundled
+//!   devices, and the [`Device`](trait.Device.html) trait (with its
+//!   [`Context`](struct.Context.html)) for devices of your own.
+//! - Save states: [`Machine::save_state`](struct.Machine.html#method.save_state) and
+//!   [`Machine::load_state`](struct.Machine.html#method.load_state) (and
+//!   [`StateError`](enum.StateError.html)); [`State`](struct.State.html) is only for the
+//!   `sync` of a `Device`. The format of a save state may change with a minor version (a
+//!   state of another format is rejected).
+//! - Test ROMs: [`TestStatus`](enum.TestStatus.html) and
+//!   [`TestResult`](struct.TestResult.html).
+//!
+//! The fields of [`Cartridge`](struct.Cartridge.html) are not part of that; load a cartridge
+//! with [`Machine::open`](struct.Machine.html#method.open) (or `Cartridge::open` and
+//! `Machine::insert`). Everything else (CPU, PPU, APU, mappers, and the bus) is internal.
+
+#![feature(type_ascription)]
+#![feature(range_contains)]
+
+#[macro_use]
+extern crate log;
+
+#[macro_use]
+extern crate bitflags;
+
+extern crate strfmt;
+
+mod controller;
+
+#[macro_use]
+mod mmu;
+
+mod bus;
+mod cpu;
+mod ppu;
+mod apu;
+mod input;
+
+mod cartridge;
+mod state;
+mod rewind;
+mod test_rom;
+
+mod machine;
+
+pub use machine::{Machine, Step, FrameStep};
+pub use cartridge::Cartridge;
+pub use cpu::{JamBehavior, Registers};
+pub use ppu::{Frame, WIDTH, HEIGHT, FRAME_RATE};
+pub use apu::{Samples, SAMPLE_RATE};
+pub use input::{Button, Adapter, PLAYERS};
+pub use input::{Device, Context, Standard, FourScore, Hori, Zapper, Vaus, PowerPad, SnesMouse};
+pub use input::POWER_PAD_BUTTONS;
+pub use state::{State, Error as StateError};
+pub use test_rom::{TestStatus, TestResult};
@@ -0,0 +1,71 @@
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Frame limiter; paces frames to a frame rate (times a speed)
+///
+/// The time of each frame is kept relative to a fixed start (instead of to the previous
+/// frame) so that oversleeping and rounding do not add up over time. When the emulation
+/// falls behind by more than a few frames it starts again from the current time (instead of
+/// running fast to catch up).
+pub struct Limiter {
+    /// Frame rate (in Hz)
+    rate: f64,
+
+    /// Start of the schedule
+    start: Option<Instant>,
+
+    /// Time of the next frame (in seconds from `start`)
+    next: f64,
+}
+
+/// Number of frames the emulation may fall behind before the schedule is started again
+const MAX_LAG: f64 = 4.0;
+
+impl Limiter {
+    pub fn new(rate: f64) -> Limiter {
+        Limiter {
+            rate: rate,
+            start: None,
+            next: 0.0,
+        }
+    }
+
+    /// Wait until it is time for the next frame (at `speed` times the frame rate).
+    pub fn wait(&mut self, speed: f64) {
+        let period = 1.0 / (self.rate * speed);
+
+        let start = match self.start {
+            Some(start) => start,
+
+            None => {
+                self.restart();
+
+                return;
+            }
+        };
+
+        self.next += period;
+
+        let now = seconds(start.elapsed());
+        if now < self.next {
+            thread::sleep(duration(self.next - now));
+        } else if now - self.next > period * MAX_LAG {
+            self.restart();
+        }
+    }
+
+    /// Start the schedule again from the current time (after a pause or when frames were
+    /// paced by something else).
+    pub fn restart(&mut self) {
+        self.start = Some(Instant::now());
+        self.next = 0.0;
+    }
+}
+
+fn seconds(duration: Duration) -> f64 {
+    duration.as_secs() as f64 + duration.subsec_nanos() as f64 * 1e-9
+}
+
+fn duration(seconds: f64) -> Duration {
+    Duration::new(seconds as u64, (seconds.fract() * 1e9) as u32)
+}
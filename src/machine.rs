@@ -2,8 +2,41 @@ use cartridge::Cartridge;
 use bus;
 use cpu;
 use ppu;
-use sdl2::keyboard::Scancode;
+use apu;
+use input;
+use state;
+use rewind::Rewind;
+use test_rom;
+use std::io;
 
+/// Result of running the machine for an instruction or a frame
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Step {
+    /// Number of CPU cycles that were run
+    pub cycles: u32,
+
+    /// An NMI was taken (also when it hijacked the sequence of an IRQ or a BRK)
+    pub nmi: bool,
+
+    /// An IRQ was taken
+    pub irq: bool,
+
+    /// Frame number (frames completed since reset) after the step
+    pub frame: u32,
+}
+
+/// Result of running the machine for a frame
+pub struct FrameStep<'a> {
+    pub step: Step,
+
+    /// Video of the frame
+    pub video: ppu::Frame<'a>,
+
+    /// Audio of the frame
+    pub audio: apu::Samples<'a>,
+}
+
+/// The NES: CPU and everything on its bus (PPU, APU, memory, cartridge, and input)
 #[derive(Default)]
 pub struct Machine {
     /// Interconnect/Bus
@@ -11,6 +44,12 @@ pub struct Machine {
 
     /// CPU
     cpu: cpu::CPU,
+
+    /// Rewind buffer (when enabled)
+    rewind: Option<Rewind>,
+
+    /// Monitor of the status protocol of test ROMs (when enabled)
+    monitor: Option<test_rom::Monitor>,
 }
 
 impl Machine {
@@ -18,33 +57,346 @@ impl Machine {
         Default::default()
     }
 
+    /// Set the callback that receives each video frame (at the start of VBlank)
     pub fn set_on_video_refresh(&mut self, callback: Box<FnMut(ppu::Frame) -> ()>) {
         self.bus.ppu.set_on_refresh(callback);
     }
 
-    pub fn on_key_down(&mut self, scancode: Scancode) {
-        self.bus.input.on_key_down(scancode);
+    /// Set the callback that receives audio samples (in batches; at the rate from
+    /// `set_sample_rate`)
+    pub fn set_on_audio_samples(&mut self, callback: Box<FnMut(apu::Samples) -> ()>) {
+        self.bus.apu.set_on_samples(callback);
+    }
+
+    /// Set the output sample rate of the audio (44100 Hz by default)
+    pub fn set_sample_rate(&mut self, rate: u32) {
+        self.bus.apu.set_sample_rate(rate);
+    }
+
+    /// Set the behavior of the CPU when it executes KIL (JAM); halts by default
+    pub fn set_jam_behavior(&mut self, behavior: cpu::JamBehavior) {
+        self.cpu.ctx.jam_behavior = behavior;
+    }
+
+    /// Connect a 4-player adapter (players 3 and 4 are only read through one)
+    pub fn set_adapter(&mut self, adapter: input::Adapter) {
+        self.bus.input.set_adapter(adapter);
+    }
+
+    /// Attach a device to a controller port (0: $4016; 1: $4017); replaces the standard
+    /// controller (or adapter) in that port
+    pub fn connect(&mut self, port: usize, device: Box<input::Device>) {
+        self.bus.input.connect(port, device);
+    }
+
+    /// Attach a device to the expansion port of the Famicom (or detach with `None`)
+    pub fn connect_expansion(&mut self, device: Option<Box<input::Device>>) {
+        self.bus.input.connect_expansion(device);
+    }
+
+    /// Set the position of the host pointer (in pixels of the frame); used by devices like
+    /// the Zapper
+    pub fn set_pointer(&mut self, x: i32, y: i32) {
+        self.bus.input.set_pointer(x, y);
+    }
+
+    /// Move the host pointer (relative motion; in pixels of the frame); used by devices
+    /// like the SNES mouse
+    pub fn move_pointer(&mut self, dx: i32, dy: i32) {
+        self.bus.input.move_pointer(dx, dy);
+    }
+
+    /// Press (or release) a key of the attached devices (for devices with more buttons
+    /// than a standard controller, like the Power Pad)
+    pub fn set_key(&mut self, key: usize, pressed: bool) {
+        self.bus.input.set_key(key, pressed);
     }
 
-    pub fn on_key_up(&mut self, scancode: Scancode) {
-        self.bus.input.on_key_up(scancode);
+    /// Press (or release) a button of the host pointer (0: primary; 1: secondary)
+    pub fn set_pointer_button(&mut self, button: usize, pressed: bool) {
+        self.bus.input.set_pointer_button(button, pressed);
     }
 
-    pub fn open(&mut self, filename: &str) {
+    /// Press a button on the controller of `player` (0 to 3)
+    pub fn press_button(&mut self, player: usize, button: input::Button) {
+        self.bus.input.press(player, button);
+    }
+
+    /// Release a button on the controller of `player` (0 to 3)
+    pub fn release_button(&mut self, player: usize, button: input::Button) {
+        self.bus.input.release(player, button);
+    }
+
+    /// Set the state of all buttons on the controller of `player` at once (one bit per
+    /// button; see `Button`)
+    pub fn set_controller_state(&mut self, player: usize, state: u8) {
+        self.bus.input.set_state(player, state);
+    }
+
+    /// Open an iNES ROM image and insert it; nothing is inserted if the image can not be
+    /// read (or is not supported)
+    pub fn open(&mut self, filename: &str) -> io::Result<()> {
         // TODO: Cleanup with `Cartridge::with_rom(...)`
         let mut cartridge: Cartridge = Default::default();
-        cartridge.open(filename);
+        cartridge.open(filename)?;
+
+        self.insert(cartridge);
 
+        Ok(())
+    }
+
+    /// Insert a cartridge; takes effect on the next reset
+    pub fn insert(&mut self, cartridge: Cartridge) {
         // Give cartridge to Bus
         self.bus.take_cartridge(cartridge);
     }
 
+    /// Reset (or power on) the machine
     pub fn reset(&mut self) {
         self.bus.reset();
         self.cpu.reset(&mut self.bus);
+
+        if let Some(ref mut rewind) = self.rewind {
+            rewind.clear();
+        }
+    }
+
+    /// Save the state of the machine (between instructions); see `load_state`
+    pub fn save_state(&mut self) -> Vec<u8> {
+        let mut s = state::State::save(self.bus.rom_hash());
+
+        self.cpu.sync(&mut s);
+        self.bus.sync(&mut s);
+
+        s.into_data()
     }
 
+    /// Load a state saved by `save_state` (with the same ROM inserted and the same devices
+    /// attached; after a reset). The state of the machine is unchanged if the state can
+    /// not be loaded.
+    pub fn load_state(&mut self, data: &[u8]) -> Result<(), state::Error> {
+        let backup = self.save_state();
+
+        let mut s = state::State::load(data, self.bus.rom_hash())?;
+
+        self.cpu.sync(&mut s);
+        self.bus.sync(&mut s);
+
+        let result = s.finish();
+        if result.is_err() {
+            let mut s = state::State::load(&backup, self.bus.rom_hash()).unwrap();
+
+            self.cpu.sync(&mut s);
+            self.bus.sync(&mut s);
+        }
+
+        result
+    }
+
+    /// Internal RAM of the CPU (2 KiB; $0000-$07FF)
+    pub fn ram(&self) -> &[u8] {
+        self.bus.ram()
+    }
+
+    /// Registers of the CPU
+    pub fn registers(&self) -> cpu::Registers {
+        self.cpu.registers()
+    }
+
+    /// Number of CPU cycles run since power on (wraps around)
+    pub fn cycles(&self) -> u32 {
+        self.cpu.cycles()
+    }
+
+    /// Number of frames completed since reset
+    pub fn frame(&self) -> u32 {
+        self.bus.ppu.frame
+    }
+
+    /// Keep a snapshot every `interval` frames (in memory; up to `budget` bytes) to be
+    /// able to go back with `rewind`
+    pub fn enable_rewind(&mut self, interval: u32, budget: usize) {
+        self.rewind = Some(Rewind::new(interval, budget));
+    }
+
+    pub fn disable_rewind(&mut self) {
+        self.rewind = None;
+    }
+
+    /// Go back `frames` frames (or as far back as the rewind buffer goes); the state is
+    /// as it was at the start of V-Blank of that frame. Returns the frame that was reached
+    /// (or `None` if rewind is disabled or nothing was recorded yet).
+    pub fn rewind(&mut self, frames: u32) -> Option<u32> {
+        let target = self.frame().saturating_sub(frames);
+
+        let mut rewind = match self.rewind.take() {
+            Some(rewind) => rewind,
+            None => return None,
+        };
+
+        let reached = match rewind.seek(target) {
+            Some((frame, data, inputs)) => {
+                if self.load_state(data).is_ok() {
+                    self.replay(&inputs);
+
+                    Some(frame + inputs.len() as u32)
+                } else {
+                    None
+                }
+            }
+
+            None => None,
+        };
+
+        // Record again from the frame that was reached
+        if reached.is_none() {
+            rewind.clear();
+        } else {
+            rewind.start_frame();
+        }
+
+        self.rewind = Some(rewind);
+
+        reached
+    }
+
+    /// Run a frame for each entry of `inputs` with the controller state of each player from
+    /// it (without output to the callbacks); the live controller state is kept.
+    fn replay(&mut self, inputs: &[[u8; input::PLAYERS]]) {
+        let on_refresh = self.bus.ppu.take_on_refresh();
+        let on_samples = self.bus.apu.take_on_samples();
+        let live = self.bus.input.states();
+
+        for states in inputs {
+            for (player, &state) in states.iter().enumerate() {
+                self.bus.input.set_state(player, state);
+            }
+
+            let frame = self.frame();
+            while self.frame() == frame {
+                self.cpu.run_next(&mut self.bus);
+            }
+        }
+
+        for (player, &state) in live.iter().enumerate() {
+            self.bus.input.set_state(player, state);
+        }
+
+        if let Some(on_refresh) = on_refresh {
+            self.bus.ppu.set_on_refresh(on_refresh);
+        }
+
+        if let Some(on_samples) = on_samples {
+            self.bus.apu.set_on_samples(on_samples);
+        }
+    }
+
+    /// Press the reset button (soft reset); RAM and the cartridge are unchanged
+    pub fn soft_reset(&mut self) {
+        self.bus.soft_reset();
+        self.cpu.soft_reset(&mut self.bus);
+    }
+
+    /// Run the next CPU instruction (or interrupt sequence)
     pub fn run(&mut self) {
-        self.cpu.run_next(&mut self.bus);
+        self.step_instruction();
+    }
+
+    /// Run the next CPU instruction (and the interrupt sequence that follows it, if any);
+    /// this is the finest step of the machine (the CPU can not be stopped within an
+    /// instruction)
+    pub fn step_instruction(&mut self) -> Step {
+        let frame = self.frame();
+        let cycles = self.cpu.cycles();
+
+        if let Some(ref mut rewind) = self.rewind {
+            rewind.record(self.bus.input.states());
+        }
+
+        let interrupt = self.cpu.run_next(&mut self.bus);
+
+        if self.frame() != frame {
+            if self.rewind.is_some() {
+                self.step_rewind();
+            }
+
+            self.step_monitor();
+        }
+
+        Step {
+            cycles: self.cpu.cycles().wrapping_sub(cycles),
+            nmi: interrupt == Some(cpu::Interrupt::NMI),
+            irq: interrupt == Some(cpu::Interrupt::IRQ),
+            frame: self.frame(),
+        }
+    }
+
+    /// Run until the PPU finishes a frame (the start of V-Blank); the frame and the audio
+    /// samples produced while running are returned (as well as handed to the callbacks)
+    pub fn run_frame<'a>(&'a mut self) -> FrameStep<'a> {
+        let frame = self.frame();
+        let mut step = Step::default();
+
+        self.bus.apu.set_capture(true);
+
+        while self.frame() == frame {
+            let instruction = self.step_instruction();
+
+            step.cycles += instruction.cycles;
+            step.nmi |= instruction.nmi;
+            step.irq |= instruction.irq;
+        }
+
+        step.frame = self.frame();
+
+        self.bus.apu.flush();
+        self.bus.apu.set_capture(false);
+
+        FrameStep {
+            step: step,
+            video: self.bus.ppu.output(),
+            audio: self.bus.apu.captured(),
+        }
+    }
+
+    /// Monitor the status of a test ROM (written to $6000 by ROMs like blargg's test ROMs);
+    /// a reset requested by the ROM is done by the machine
+    pub fn enable_test_monitor(&mut self) {
+        self.monitor = Some(test_rom::Monitor::new());
+    }
+
+    /// Status of the test ROM (if the monitor is enabled)
+    pub fn test_status(&self) -> Option<&test_rom::TestStatus> {
+        self.monitor.as_ref().map(|monitor| monitor.status())
+    }
+
+    /// Start of a frame: check the status of the test ROM (and reset if requested)
+    fn step_monitor(&mut self) {
+        let reset = match self.monitor {
+            Some(ref mut monitor) => monitor.step(&mut self.bus),
+            None => false,
+        };
+
+        if reset {
+            self.soft_reset();
+        }
+    }
+
+    /// Start of a frame: take a snapshot (if due); the input of the frame is recorded before
+    /// its first instruction (the frontend sets it after the end of the last frame)
+    fn step_rewind(&mut self) {
+        let frame = self.frame();
+
+        if self.rewind.as_ref().map_or(false, |rewind| rewind.due(frame)) {
+            let data = self.save_state();
+
+            if let Some(ref mut rewind) = self.rewind {
+                rewind.push(frame, data);
+            }
+        }
+
+        if let Some(ref mut rewind) = self.rewind {
+            rewind.start_frame();
+        }
     }
 }
@@ -1,36 +1,130 @@
-#![feature(type_ascription)]
-#![feature(range_contains)]
-
 extern crate sdl2;
+extern crate toml;
 
 #[macro_use]
 extern crate log;
 extern crate env_logger;
 
-#[macro_use]
-extern crate bitflags;
+extern crate lintel_nes;
 
-extern crate strfmt;
+mod bindings;
+mod limiter;
+
+use bindings::{Bindings, PortDevice};
+use limiter::Limiter;
+use lintel_nes::{Machine, Device, Zapper, Vaus, PowerPad, SnesMouse};
+use sdl2::audio::{AudioQueue, AudioSpecDesired};
+use sdl2::controller::GameController;
+use sdl2::event::Event;
+use sdl2::keyboard::{Scancode, LSHIFTMOD, RSHIFTMOD};
+use sdl2::mouse::MouseButton;
+use sdl2::pixels::Color;
+use sdl2::video::WindowBuilder;
+use sdl2::render::RendererBuilder;
+use std::fs::File;
+use std::io::{Read, Write};
+use std::mem;
+use std::thread;
+use std::time::Duration;
 
-mod controller;
+/// Scale of the window (relative to the frame)
+const SCALE: u32 = 2;
 
-#[macro_use]
-mod mmu;
+/// Create a device for a controller port (or the expansion port)
+fn create_device(device: PortDevice, expansion: bool) -> Option<Box<Device>> {
+    match device {
+        PortDevice::Controller => None,
+        PortDevice::Zapper => Some(Box::new(Zapper::new())),
+        PortDevice::Vaus if expansion => Some(Box::new(Vaus::famicom())),
+        PortDevice::Vaus => Some(Box::new(Vaus::new())),
+        PortDevice::PowerPad if expansion => Some(Box::new(PowerPad::family_trainer())),
+        PortDevice::PowerPad => Some(Box::new(PowerPad::new())),
+        PortDevice::SnesMouse => Some(Box::new(SnesMouse::new())),
+    }
+}
 
-mod bus;
-mod cpu;
-mod ppu;
-mod apu;
-mod input;
+/// Key held to rewind
+const REWIND_KEY: Scancode = Scancode::Grave;
 
-mod cartridge;
+/// Key to pause (and resume)
+const PAUSE_KEY: Scancode = Scancode::P;
 
-mod machine;
+/// Key to run a single frame (pauses if running)
+const FRAME_ADVANCE_KEY: Scancode = Scancode::O;
 
-use sdl2::event::Event;
-use sdl2::pixels::Color;
-use sdl2::video::WindowBuilder;
-use sdl2::render::RendererBuilder;
+/// Key held to fast-forward
+const FAST_FORWARD_KEY: Scancode = Scancode::Tab;
+
+/// Key held for slow motion
+const SLOW_MOTION_KEY: Scancode = Scancode::Minus;
+
+/// Latency of the audio queue (in seconds); at normal speed frames are paced by the audio
+/// device (the emulation waits for the queue to drain down to this)
+const AUDIO_LATENCY: f64 = 0.05;
+
+/// Number of frames between snapshots of the rewind buffer
+const REWIND_INTERVAL: u32 = 10;
+
+/// Memory budget of the rewind buffer (in bytes)
+const REWIND_BUDGET: usize = 64 * 1024 * 1024;
+
+/// Number of samples in the audio queue
+fn queued(queue: &AudioQueue<f32>) -> usize {
+    queue.size() as usize / mem::size_of::<f32>()
+}
+
+/// Save state slot (1-10) of a key; F1-F10 load a slot and Shift+F1-F10 save to it
+fn state_slot(scancode: Scancode) -> Option<usize> {
+    match scancode {
+        Scancode::F1 => Some(1),
+        Scancode::F2 => Some(2),
+        Scancode::F3 => Some(3),
+        Scancode::F4 => Some(4),
+        Scancode::F5 => Some(5),
+        Scancode::F6 => Some(6),
+        Scancode::F7 => Some(7),
+        Scancode::F8 => Some(8),
+        Scancode::F9 => Some(9),
+        Scancode::F10 => Some(10),
+
+        _ => None,
+    }
+}
+
+/// Save the state of the machine to a slot (`<rom>.state<slot>`)
+fn save_state(m: &mut Machine, rom: &str, slot: usize) {
+    let filename = format!("{}.state{}", rom, slot);
+    let data = m.save_state();
+
+    match File::create(&filename).and_then(|mut file| file.write_all(&data)) {
+        Ok(()) => info!("saved state to slot {}", slot),
+        Err(error) => warn!("failed to save state to {}: {}", filename, error),
+    }
+}
+
+/// Load the state of the machine from a slot (`<rom>.state<slot>`)
+fn load_state(m: &mut Machine, rom: &str, slot: usize) {
+    let filename = format!("{}.state{}", rom, slot);
+    let mut data = Vec::new();
+
+    if let Err(error) = File::open(&filename).and_then(|mut file| file.read_to_end(&mut data)) {
+        warn!("failed to load state from {}: {}", filename, error);
+
+        return;
+    }
+
+    match m.load_state(&data) {
+        Ok(()) => info!("loaded state from slot {}", slot),
+        Err(error) => warn!("failed to load state from {}: {}", filename, error),
+    }
+}
+
+/// Player that a game controller (by instance ID) is assigned to
+fn gamepad_player(gamepads: &[Option<GameController>], which: i32) -> Option<usize> {
+    gamepads.iter().position(|gamepad| {
+        gamepad.as_ref().map_or(false, |gamepad| gamepad.instance_id() == which)
+    })
+}
 
 fn main() {
     // Log: Initialize (level set from environment variables)
@@ -40,16 +134,34 @@ fn main() {
     let c = sdl2::init().unwrap();
     let mut events = c.event_pump().unwrap();
     let video = c.video().unwrap();
+    let audio = c.audio().unwrap();
+    let controllers = c.game_controller().unwrap();
     let mut is_running = true;
+    let mut is_rewinding = false;
+    let mut is_paused = false;
+    let mut is_fast_forwarding = false;
+    let mut is_slow_motion = false;
+
+    // Run a single frame (while paused)
+    let mut advance = false;
+
+    // Load bindings of keyboard and game controllers
+    let bindings = Bindings::load();
+
+    // Game controllers (assigned to players as they are connected)
+    let mut gamepads: [Option<GameController>; lintel_nes::PLAYERS] = [None, None, None, None];
 
     // Create window
-    let width = ppu::WIDTH as u32;
-    let height = ppu::HEIGHT as u32;
-    let window = WindowBuilder::new(&video, "Lintel", width * 2, height * 2).build().unwrap();
+    let width = lintel_nes::WIDTH as u32;
+    let height = lintel_nes::HEIGHT as u32;
+    let window = WindowBuilder::new(&video, "Lintel", width * SCALE, height * SCALE)
+        .build()
+        .unwrap();
 
     // Create 2D renderer
-    // TODO: Do not use present_vsync and instead limit frame rate manually
-    let mut renderer = RendererBuilder::new(window).accelerated().present_vsync().build().unwrap();
+    // NOTE: Not synced to the display (frames are paced by the audio queue or the frame
+    //       limiter); the refresh rate of the display may be anything
+    let mut renderer = RendererBuilder::new(window).accelerated().build().unwrap();
 
     // Initially clear the renderer
     renderer.set_draw_color(Color::RGB(0, 0, 0));
@@ -61,35 +173,98 @@ fn main() {
         renderer.create_texture_streaming(sdl2::pixels::PixelFormatEnum::ARGB8888, width, height)
             .unwrap();
 
-    let mut m = machine::Machine::new();
+    // Create audio queue (mono; f32)
+    let desired = AudioSpecDesired {
+        freq: Some(lintel_nes::SAMPLE_RATE as i32),
+        channels: Some(1),
+        samples: None,
+    };
 
-    m.set_on_video_refresh(Box::new(move |frame| {
-        renderer.clear();
+    let queue = match audio.open_queue::<f32>(None, &desired) {
+        Ok(queue) => Some(queue),
 
-        // Render: Update texture and flip
-        texture.update(None, frame.data, frame.pitch).unwrap();
-        renderer.copy(&texture, None, None).unwrap();
+        Err(error) => {
+            warn!("failed to open audio device: {}", error);
 
-        // Render: Present
-        renderer.present();
-    }));
+            None
+        }
+    };
+
+    let sample_rate = queue.as_ref().map_or(lintel_nes::SAMPLE_RATE, |queue| {
+        queue.resume();
+        queue.spec().freq as u32
+    });
+
+    let latency = (sample_rate as f64 * AUDIO_LATENCY) as usize;
+
+    // Frame limiter (used when not paced by audio)
+    let mut limiter = Limiter::new(lintel_nes::FRAME_RATE);
+
+    let mut m = Machine::new();
+    m.set_sample_rate(sample_rate);
+    m.set_adapter(bindings.adapter);
+
+    for (port, &device) in bindings.ports.iter().enumerate() {
+        if let Some(device) = create_device(device, false) {
+            m.connect(port, device);
+        }
+    }
+
+    if bindings.expansion != PortDevice::Controller {
+        m.connect_expansion(create_device(bindings.expansion, true));
+    }
+
+    let rom = std::env::args().nth(1).unwrap();
+    if let Err(error) = m.open(&rom) {
+        error!("failed to open {}: {}", rom, error);
+
+        return;
+    }
 
-    m.open(&std::env::args().nth(1).unwrap());
     m.reset();
+    m.enable_rewind(REWIND_INTERVAL, REWIND_BUDGET);
 
     while is_running {
-        // Poll events
-        if let Some(evt) = events.poll_event() {
+        // Poll events (all that arrived during the last frame)
+        for evt in events.poll_iter() {
             match evt {
                 Event::Quit { .. } => {
                     // Quit the program
                     is_running = false;
                 }
 
-                Event::KeyDown { scancode, repeat, .. } => {
+                Event::KeyDown { scancode, keymod, repeat, .. } => {
                     if !repeat {
                         if let Some(scancode) = scancode {
-                            m.on_key_down(scancode);
+                            if let Some(slot) = state_slot(scancode) {
+                                if keymod.intersects(LSHIFTMOD | RSHIFTMOD) {
+                                    save_state(&mut m, &rom, slot);
+                                } else {
+                                    load_state(&mut m, &rom, slot);
+                                }
+                            }
+
+                            match scancode {
+                                REWIND_KEY => is_rewinding = true,
+                                FAST_FORWARD_KEY => is_fast_forwarding = true,
+                                SLOW_MOTION_KEY => is_slow_motion = true,
+                                PAUSE_KEY => is_paused = !is_paused,
+
+                                FRAME_ADVANCE_KEY => {
+                                    is_paused = true;
+                                    advance = true;
+                                }
+
+                                _ => {}
+                            }
+
+                            for &(player, button) in bindings.key(scancode) {
+                                m.press_button(player, button);
+                            }
+
+                            for &key in bindings.power_pad_key(scancode) {
+                                m.set_key(key, true);
+                            }
                         }
                     }
                 }
@@ -97,7 +272,104 @@ fn main() {
                 Event::KeyUp { scancode, repeat, .. } => {
                     if !repeat {
                         if let Some(scancode) = scancode {
-                            m.on_key_up(scancode);
+                            match scancode {
+                                REWIND_KEY => is_rewinding = false,
+                                FAST_FORWARD_KEY => is_fast_forwarding = false,
+                                SLOW_MOTION_KEY => is_slow_motion = false,
+
+                                _ => {}
+                            }
+
+                            for &(player, button) in bindings.key(scancode) {
+                                m.release_button(player, button);
+                            }
+
+                            for &key in bindings.power_pad_key(scancode) {
+                                m.set_key(key, false);
+                            }
+                        }
+                    }
+                }
+
+                Event::MouseMotion { x, y, xrel, yrel, .. } => {
+                    m.set_pointer(x / SCALE as i32, y / SCALE as i32);
+                    m.move_pointer(xrel, yrel);
+                }
+
+                Event::MouseButtonDown { mouse_btn, .. } => {
+                    match mouse_btn {
+                        MouseButton::Left => m.set_pointer_button(0, true),
+                        MouseButton::Right => m.set_pointer_button(1, true),
+
+                        _ => {}
+                    }
+                }
+
+                Event::MouseButtonUp { mouse_btn, .. } => {
+                    match mouse_btn {
+                        MouseButton::Left => m.set_pointer_button(0, false),
+                        MouseButton::Right => m.set_pointer_button(1, false),
+
+                        _ => {}
+                    }
+                }
+
+                Event::ControllerDeviceAdded { which, .. } => {
+                    // Assign the game controller to the first player without one
+                    if let Some(player) = gamepads.iter().position(|gamepad| gamepad.is_none()) {
+                        match controllers.open(which as u32) {
+                            Ok(gamepad) => {
+                                info!("game controller \"{}\" connected (player {})",
+                                      gamepad.name(),
+                                      player + 1);
+
+                                gamepads[player] = Some(gamepad);
+                            }
+
+                            Err(error) => {
+                                warn!("failed to open game controller {}: {}", which, error);
+                            }
+                        }
+                    }
+                }
+
+                Event::ControllerDeviceRemoved { which, .. } => {
+                    if let Some(player) = gamepad_player(&gamepads, which) {
+                        info!("game controller disconnected (player {})", player + 1);
+
+                        gamepads[player] = None;
+
+                        // Release anything that was held on the game controller
+                        for button in bindings.controller_buttons(player) {
+                            m.release_button(player, button);
+                        }
+                    }
+                }
+
+                Event::ControllerButtonDown { which, button, .. } => {
+                    if let Some(player) = gamepad_player(&gamepads, which) {
+                        for button in bindings.controller_button(player, button) {
+                            m.press_button(player, button);
+                        }
+                    }
+                }
+
+                Event::ControllerButtonUp { which, button, .. } => {
+                    if let Some(player) = gamepad_player(&gamepads, which) {
+                        for button in bindings.controller_button(player, button) {
+                            m.release_button(player, button);
+                        }
+                    }
+                }
+
+                Event::ControllerAxisMotion { which, axis, value, .. } => {
+                    if let Some(player) = gamepad_player(&gamepads, which) {
+                        for (button, pressed) in bindings.controller_axis(player, axis, value) {
+                            if pressed {
+                                m.press_button(player, button);
+                            } else {
+                                m.release_button(player, button);
+                            }
                         }
                     }
                 }
@@ -108,7 +380,68 @@ fn main() {
             }
         }
 
-        // Run: Machine
-        m.run();
+        if is_paused && !advance {
+            // Wait a frame (to poll events again)
+            limiter.wait(1.0);
+
+            continue;
+        }
+
+        advance = false;
+
+        let speed = if is_fast_forwarding {
+            bindings.fast_forward
+        } else if is_slow_motion {
+            bindings.slow_motion
+        } else {
+            1.0
+        };
+
+        // Rewind: go back 2 frames before each frame is run (to play backwards at
+        // normal speed)
+        if is_rewinding {
+            m.rewind(2);
+        }
+
+        // Run: Machine (for a frame)
+        let output = m.run_frame();
+
+        // Render: Update texture and flip
+        renderer.clear();
+        texture.update(None, output.video.data, output.video.pitch).unwrap();
+        renderer.copy(&texture, None, None).unwrap();
+
+        // Render: Present
+        renderer.present();
+
+        if let Some(ref queue) = queue {
+            // Drop samples (instead of letting latency grow) when running faster than
+            // normal
+            if queued(queue) < latency * 2 {
+                queue.queue(output.audio.data);
+            }
+        }
+
+        // Pace: by the audio queue at normal speed (so that the emulation runs at the
+        // rate of the audio device and the queue neither runs dry nor grows); otherwise
+        // (or without audio) by the frame limiter
+        match queue {
+            Some(ref queue) if speed == 1.0 && !is_paused => {
+                // NOTE: Gives up after ~100 ms (in case the device is not playing)
+                for _ in 0..100 {
+                    if queued(queue) <= latency {
+                        break;
+                    }
+
+                    thread::sleep(Duration::from_millis(1));
+                }
+
+                limiter.restart();
+            }
+
+            _ => {
+                limiter.wait(speed);
+            }
+        }
     }
 }
@@ -2,6 +2,7 @@ use cpu;
 use ppu;
 use cartridge::Cartridge;
 use controller;
+use state::State;
 
 /// Used to generate sub-controller trait definitions in the CPU and PPU.
 macro_rules! make_controller {() => {
@@ -75,6 +76,48 @@ impl MMU {
             controller.reset();
         }
     }
+
+    pub fn step(&mut self) {
+        if let Some(ref mut controller) = self.controller {
+            controller.step();
+        }
+    }
+
+    /// IRQ (signal) from the active memory controller
+    pub fn irq(&self) -> bool {
+        if let Some(ref controller) = self.controller {
+            return controller.irq();
+        }
+
+        false
+    }
+
+    /// [CPU] Internal RAM
+    pub fn cpu_ram(&self) -> &[u8] {
+        &self.cpu_ram
+    }
+
+    /// Hash of the ROM in the cartridge
+    pub fn rom_hash(&self) -> u32 {
+        self.cartridge.hash
+    }
+
+    /// Save (or load) the state of RAM (internal and in the cartridge) and of the active
+    /// memory controller.
+    pub fn sync(&mut self, s: &mut State) {
+        s.slice(&mut self.cpu_ram);
+        s.slice(&mut self.ppu_ram);
+        s.slice(&mut self.ppu_palette);
+        s.slice(&mut self.cartridge.prg_ram);
+
+        if self.cartridge.chr_mutable {
+            s.slice(&mut self.cartridge.chr);
+        }
+
+        if let Some(ref mut controller) = self.controller {
+            controller.sync(s);
+        }
+    }
 }
 
 impl cpu::Controller for MMU {
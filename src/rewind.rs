@@ -0,0 +1,237 @@
+use std::collections::VecDeque;
+
+use input::PLAYERS;
+
+/// Snapshot in the rewind buffer (other than the newest)
+struct Snapshot {
+    /// Frame (of the PPU) the snapshot was taken at
+    frame: u32,
+
+    /// State; delta against the next (newer) snapshot (see `encode`)
+    delta: Vec<u8>,
+
+    /// Controller state of each player for each frame from this snapshot to the next
+    inputs: Vec<[u8; PLAYERS]>,
+}
+
+/// Rewind buffer
+///
+/// Holds a snapshot (save state) of the machine taken every `interval` frames. Only the
+/// newest snapshot is kept whole; each older snapshot is kept as the difference to the
+/// snapshot after it (most of the state does not change in a few frames, so this is
+/// small). The oldest snapshots are dropped to stay within the memory budget.
+///
+/// The controller state of each frame is recorded as well; to go back to a frame between
+/// two snapshots the older snapshot is loaded and the frames after it are run again with
+/// the recorded input.
+///
+/// NOTE: Input is recorded once per frame (before the first instruction of the frame); a
+///       frame that is run again sees the input as it was at the start of that frame.
+pub struct Rewind {
+    /// Number of frames between snapshots
+    interval: u32,
+
+    /// Memory budget (in bytes)
+    budget: usize,
+
+    /// Older snapshots (oldest first)
+    history: VecDeque<Snapshot>,
+
+    /// Size of the older snapshots (in bytes)
+    size: usize,
+
+    /// Newest snapshot (whole; empty until the first snapshot)
+    latest: Vec<u8>,
+
+    /// Frame the newest snapshot was taken at
+    latest_frame: u32,
+
+    /// Controller state of each player for each frame since the newest snapshot
+    inputs: Vec<[u8; PLAYERS]>,
+
+    /// A frame has started; its input is yet to be recorded
+    started: bool,
+}
+
+impl Rewind {
+    pub fn new(interval: u32, budget: usize) -> Rewind {
+        Rewind {
+            interval: if interval == 0 { 1 } else { interval },
+            budget: budget,
+            history: VecDeque::new(),
+            size: 0,
+            latest: Vec::new(),
+            latest_frame: 0,
+            inputs: Vec::new(),
+            started: false,
+        }
+    }
+
+    /// A snapshot is due at `frame`
+    pub fn due(&self, frame: u32) -> bool {
+        self.latest.is_empty() || frame.wrapping_sub(self.latest_frame) >= self.interval
+    }
+
+    /// Add a snapshot (taken at `frame`).
+    pub fn push(&mut self, frame: u32, data: Vec<u8>) {
+        if !self.latest.is_empty() {
+            let snapshot = Snapshot {
+                frame: self.latest_frame,
+                delta: encode(&self.latest, &data),
+                inputs: self.inputs.split_off(0),
+            };
+
+            self.size += snapshot.delta.len() + snapshot.inputs.len() * PLAYERS;
+            self.history.push_back(snapshot);
+        }
+
+        self.latest = data;
+        self.latest_frame = frame;
+        self.inputs.clear();
+
+        // Drop the oldest snapshots until the buffer fits in the budget
+        while self.size + self.latest.len() > self.budget {
+            match self.history.pop_front() {
+                Some(snapshot) => {
+                    self.size -= snapshot.delta.len() + snapshot.inputs.len() * PLAYERS;
+                }
+
+                None => break,
+            }
+        }
+    }
+
+    /// A frame has started (after its snapshot, if any); its input is recorded by the next
+    /// `record`.
+    pub fn start_frame(&mut self) {
+        self.started = true;
+    }
+
+    /// Record the controller state of each player (before each instruction); only the
+    /// first after `start_frame` is kept as the input of the frame.
+    pub fn record(&mut self, inputs: [u8; PLAYERS]) {
+        if self.started && !self.latest.is_empty() {
+            self.inputs.push(inputs);
+        }
+
+        self.started = false;
+    }
+
+    /// Go back to the newest snapshot at or before `frame` (or the oldest snapshot); drops
+    /// everything after it. Returns the snapshot with the frame it was taken at and the
+    /// recorded input of the frames from it to `frame`.
+    pub fn seek(&mut self, frame: u32) -> Option<(u32, &[u8], Vec<[u8; PLAYERS]>)> {
+        if self.latest.is_empty() {
+            return None;
+        }
+
+        while self.latest_frame > frame {
+            match self.history.pop_back() {
+                Some(snapshot) => {
+                    self.size -= snapshot.delta.len() + snapshot.inputs.len() * PLAYERS;
+
+                    self.latest = decode(&self.latest, &snapshot.delta);
+                    self.latest_frame = snapshot.frame;
+                    self.inputs = snapshot.inputs;
+                }
+
+                None => break,
+            }
+        }
+
+        let frames = frame.saturating_sub(self.latest_frame) as usize;
+        self.inputs.truncate(frames);
+
+        Some((self.latest_frame, &self.latest, self.inputs.clone()))
+    }
+
+    pub fn clear(&mut self) {
+        self.history.clear();
+        self.size = 0;
+        self.latest.clear();
+        self.inputs.clear();
+        self.started = false;
+    }
+}
+
+/// Difference of `data` to `base`: the length of `data`, then runs of unchanged bytes and
+/// of changed bytes (XOR of the two); lengths are variable-length integers (7 bits per
+/// byte; least significant first).
+fn encode(data: &[u8], base: &[u8]) -> Vec<u8> {
+    let mut r = Vec::new();
+    write_length(&mut r, data.len());
+
+    let diff = |index: usize| data[index] ^ base.get(index).cloned().unwrap_or(0);
+
+    let mut index = 0;
+    while index < data.len() {
+        let start = index;
+        while index < data.len() && diff(index) == 0 {
+            index += 1;
+        }
+
+        write_length(&mut r, index - start);
+
+        let start = index;
+        while index < data.len() && diff(index) != 0 {
+            index += 1;
+        }
+
+        write_length(&mut r, index - start);
+        for i in start..index {
+            r.push(diff(i));
+        }
+    }
+
+    r
+}
+
+/// Undo `encode`; `base` is the same as it was given to `encode`.
+fn decode(base: &[u8], delta: &[u8]) -> Vec<u8> {
+    let mut offset = 0;
+    let len = read_length(delta, &mut offset);
+
+    let mut r = base[..len.min(base.len())].to_vec();
+    r.resize(len, 0);
+
+    let mut index = 0;
+    while offset < delta.len() {
+        index += read_length(delta, &mut offset);
+
+        let changed = read_length(delta, &mut offset);
+        for i in 0..changed {
+            r[index + i] ^= delta[offset + i];
+        }
+
+        index += changed;
+        offset += changed;
+    }
+
+    r
+}
+
+fn write_length(r: &mut Vec<u8>, mut value: usize) {
+    while value >= 0x80 {
+        r.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+
+    r.push(value as u8);
+}
+
+fn read_length(data: &[u8], offset: &mut usize) -> usize {
+    let mut value = 0;
+    let mut shift = 0;
+
+    loop {
+        let byte = data[*offset];
+        *offset += 1;
+
+        value |= ((byte & 0x7F) as usize) << shift;
+        shift += 7;
+
+        if byte & 0x80 == 0 {
+            return value;
+        }
+    }
+}
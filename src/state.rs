@@ -0,0 +1,248 @@
+use std::error;
+use std::fmt;
+
+/// Identifies a save state
+const MAGIC: &'static [u8; 4] = b"LNES";
+
+/// Version of the format; increment on any change to the fields that are synced (or
+/// their order)
+pub const VERSION: u32 = 2;
+
+#[derive(Debug, PartialEq)]
+pub enum Error {
+    /// Not a save state
+    Format,
+
+    /// Save state from an unsupported version of the format
+    Version(u32),
+
+    /// Save state of a different ROM (hash of the ROM of the save state)
+    Rom(u32),
+
+    /// Save state ends early
+    Truncated,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::Format => write!(f, "not a save state"),
+            Error::Version(version) => write!(f, "unsupported save state version {}", version),
+            Error::Rom(hash) => write!(f, "save state is for another ROM ({:08X})", hash),
+            Error::Truncated => write!(f, "save state is truncated"),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn description(&self) -> &str {
+        "invalid save state"
+    }
+}
+
+/// Save (or load) state
+///
+/// Each component _syncs_ its fields, in a fixed order, through the same methods when
+/// saving and when loading (so the two can not disagree on the format). Saving appends the
+/// value of each field to the state; loading replaces the value of each field from the
+/// state.
+///
+/// Header: magic ("LNES"), version (u32), and the hash of the ROM (u32); all values are
+/// little-endian.
+pub struct State<'a> {
+    /// Saved state (when saving)
+    data: Vec<u8>,
+
+    /// State to load (when loading)
+    input: &'a [u8],
+
+    /// Offset of the next value in `input`
+    offset: usize,
+
+    loading: bool,
+
+    /// Set when loading reads past the end of `input`
+    truncated: bool,
+
+    /// Set when loading finds a value that does not fit its field
+    invalid: bool,
+}
+
+impl<'a> State<'a> {
+    /// Start saving state (of the machine with a ROM of `rom_hash`).
+    pub fn save(rom_hash: u32) -> State<'static> {
+        let mut s = State {
+            data: Vec::new(),
+            input: &[],
+            offset: 0,
+            loading: false,
+            truncated: false,
+            invalid: false,
+        };
+
+        s.data.extend_from_slice(MAGIC);
+
+        let mut version = VERSION;
+        s.u32(&mut version);
+
+        let mut rom_hash = rom_hash;
+        s.u32(&mut rom_hash);
+
+        s
+    }
+
+    /// Start loading state (into the machine with a ROM of `rom_hash`); the header is
+    /// checked before anything is loaded.
+    pub fn load(data: &'a [u8], rom_hash: u32) -> Result<State<'a>, Error> {
+        if data.len() < 12 || &data[..4] != MAGIC {
+            return Err(Error::Format);
+        }
+
+        let mut s = State {
+            data: Vec::new(),
+            input: data,
+            offset: 4,
+            loading: true,
+            truncated: false,
+            invalid: false,
+        };
+
+        let mut version = 0;
+        s.u32(&mut version);
+        if version != VERSION {
+            return Err(Error::Version(version));
+        }
+
+        let mut hash = 0;
+        s.u32(&mut hash);
+        if hash != rom_hash {
+            return Err(Error::Rom(hash));
+        }
+
+        Ok(s)
+    }
+
+    pub fn is_loading(&self) -> bool {
+        self.loading
+    }
+
+    /// Finish saving; the saved state.
+    pub fn into_data(self) -> Vec<u8> {
+        self.data
+    }
+
+    /// Finish loading.
+    pub fn finish(self) -> Result<(), Error> {
+        if self.truncated {
+            Err(Error::Truncated)
+        } else if self.invalid {
+            Err(Error::Format)
+        } else {
+            Ok(())
+        }
+    }
+
+    pub fn slice(&mut self, value: &mut [u8]) {
+        if !self.loading {
+            self.data.extend_from_slice(value);
+        } else if self.offset + value.len() > self.input.len() {
+            self.truncated = true;
+        } else {
+            value.copy_from_slice(&self.input[self.offset..self.offset + value.len()]);
+            self.offset += value.len();
+        }
+    }
+
+    /// Data prefixed by its length; when loading, the length must be the current length of
+    /// `value` (the length in a state can not be trusted to size a buffer).
+    pub fn vec(&mut self, value: &mut Vec<u8>) {
+        let mut len = value.len() as u32;
+        self.u32(&mut len);
+
+        if self.loading && len as usize != value.len() {
+            self.invalid = true;
+            return;
+        }
+
+        self.slice(value);
+    }
+
+    pub fn u8(&mut self, value: &mut u8) {
+        let mut bytes = [*value];
+        self.slice(&mut bytes);
+
+        *value = bytes[0];
+    }
+
+    pub fn bool(&mut self, value: &mut bool) {
+        let mut byte = *value as u8;
+        self.u8(&mut byte);
+
+        *value = byte != 0;
+    }
+
+    pub fn u16(&mut self, value: &mut u16) {
+        let mut bytes = [*value as u8, (*value >> 8) as u8];
+        self.slice(&mut bytes);
+
+        *value = bytes[0] as u16 | ((bytes[1] as u16) << 8);
+    }
+
+    pub fn u32(&mut self, value: &mut u32) {
+        let mut lo = *value as u16;
+        let mut hi = (*value >> 16) as u16;
+        self.u16(&mut lo);
+        self.u16(&mut hi);
+
+        *value = lo as u32 | ((hi as u32) << 16);
+    }
+
+    pub fn i32(&mut self, value: &mut i32) {
+        let mut bits = *value as u32;
+        self.u32(&mut bits);
+
+        *value = bits as i32;
+    }
+}
+
+/// Hash (CRC-32) of the contents of a ROM
+pub fn hash(data: &[&[u8]]) -> u32 {
+    let mut crc = 0xFFFF_FFFFu32;
+    for &bytes in data {
+        for &byte in bytes {
+            crc ^= byte as u32;
+            for _ in 0..8 {
+                crc = if crc & 1 != 0 { (crc >> 1) ^ 0xEDB8_8320 } else { crc >> 1 };
+            }
+        }
+    }
+
+    !crc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Error, State};
+
+    #[test]
+    fn vec_length() {
+        let mut s = State::save(0);
+        let mut value = vec![1, 2, 3];
+        s.vec(&mut value);
+        let data = s.into_data();
+
+        let mut same = vec![0; 3];
+        let mut s = State::load(&data, 0).unwrap();
+        s.vec(&mut same);
+        assert_eq!(s.finish(), Ok(()));
+        assert_eq!(same, value);
+
+        for &len in &[0, 2, 4] {
+            let mut other = vec![0; len];
+            let mut s = State::load(&data, 0).unwrap();
+            s.vec(&mut other);
+            assert_eq!(s.finish(), Err(Error::Format));
+            assert_eq!(other, vec![0; len]);
+        }
+    }
+}
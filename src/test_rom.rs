@@ -0,0 +1,153 @@
+use bus::Bus;
+
+/// Signature at $6001-$6003 that marks the status protocol as in use
+const SIGNATURE: [u8; 3] = [0xDE, 0xB0, 0x61];
+
+/// Frames to wait before a requested reset (the ROM asks for at least 100 ms)
+const RESET_DELAY: u32 = 7;
+
+/// Longest message that is read from $6004
+const MESSAGE_LIMIT: u16 = 0x1FFC;
+
+/// Result of a test ROM
+#[derive(Clone, Debug, PartialEq)]
+pub struct TestResult {
+    /// Result code (0: passed; otherwise the number of the test that failed, or 1)
+    pub code: u8,
+
+    /// Text output by the ROM (from $6004)
+    pub message: String,
+}
+
+impl TestResult {
+    pub fn passed(&self) -> bool {
+        self.code == 0
+    }
+}
+
+/// Status of a test ROM (as reported at $6000)
+#[derive(Clone, Debug, PartialEq)]
+pub enum TestStatus {
+    /// No signature (yet); the ROM does not use the protocol (or has not started)
+    Unknown,
+
+    /// $80: Running
+    Running,
+
+    /// $81: Waiting for a reset (the reset button to be pressed)
+    ResetRequested,
+
+    /// $00-$7F: Finished
+    Finished(TestResult),
+}
+
+/// Monitor of the status protocol of test ROMs (as used by blargg's test ROMs)
+///
+/// The ROM writes its status to $6000 (in PRG-RAM) once $6001-$6003 hold the signature
+/// ($DE $B0 $61): $80 while running, $81 when it needs the reset button to be pressed (after
+/// at least 100 ms), or the result code when finished. $6004 holds the text output of the
+/// ROM (terminated by $00).
+///
+/// The monitor is checked at the start of each frame.
+pub struct Monitor {
+    status: TestStatus,
+
+    /// Frames until the requested reset
+    reset_delay: u32,
+}
+
+impl Monitor {
+    pub fn new() -> Monitor {
+        Monitor {
+            status: TestStatus::Unknown,
+            reset_delay: 0,
+        }
+    }
+
+    pub fn status(&self) -> &TestStatus {
+        &self.status
+    }
+
+    /// Check the status; returns true when the machine should be reset (soft).
+    pub fn step(&mut self, b: &mut Bus) -> bool {
+        let signature = [b.peek(0x6001), b.peek(0x6002), b.peek(0x6003)];
+        if signature != SIGNATURE {
+            self.status = TestStatus::Unknown;
+
+            return false;
+        }
+
+        let mut reset = false;
+        let status = match b.peek(0x6000) {
+            0x80 => TestStatus::Running,
+
+            0x81 => {
+                if self.status != TestStatus::ResetRequested {
+                    self.reset_delay = RESET_DELAY;
+                } else if self.reset_delay > 0 {
+                    self.reset_delay -= 1;
+                }
+
+                if self.reset_delay == 0 {
+                    reset = true;
+                }
+
+                TestStatus::ResetRequested
+            }
+
+            code @ 0x00...0x7F => {
+                TestStatus::Finished(TestResult {
+                    code: code,
+                    message: message(b),
+                })
+            }
+
+            code => {
+                warn!("test ROM: unknown status ${:02X}", code);
+
+                self.status.clone()
+            }
+        };
+
+        if status != self.status {
+            match status {
+                TestStatus::Finished(ref result) if result.passed() => {
+                    info!("test ROM: passed\n{}", result.message);
+                }
+
+                TestStatus::Finished(ref result) => {
+                    info!("test ROM: failed ({})\n{}", result.code, result.message);
+                }
+
+                ref status => {
+                    debug!("test ROM: {:?}", status);
+                }
+            }
+        }
+
+        if reset {
+            // The ROM writes $80 again once it is running after the reset
+            self.status = TestStatus::Running;
+        } else {
+            self.status = status;
+        }
+
+        reset
+    }
+}
+
+/// Text output by the ROM (at $6004)
+fn message(b: &mut Bus) -> String {
+    let mut bytes = Vec::new();
+
+    for offset in 0..MESSAGE_LIMIT {
+        let c = b.peek(0x6004 + offset);
+        if c == 0x00 {
+            break;
+        }
+
+        bytes.push(c);
+    }
+
+    String::from_utf8_lossy(&bytes).into_owned()
+}
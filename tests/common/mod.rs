@@ -0,0 +1,23 @@
+//! Shared by the tests that run a small program of their own
+
+use lintel_nes::Cartridge;
+use std::io::Cursor;
+
+/// NROM cartridge with `program` at $C000 (16 KiB of PRG-ROM, mirrored at $8000) and 8 KiB
+/// of CHR-RAM; RESET points at $C000, NMI at `nmi`, and IRQ (and BRK) at `irq`
+pub fn nrom(program: &[u8], nmi: u16, irq: u16) -> Cartridge {
+    let mut prg_rom = vec![0u8; 0x4000];
+    prg_rom[..program.len()].copy_from_slice(program);
+
+    // NMI, RESET, and IRQ vectors
+    prg_rom[0x3FFA..].copy_from_slice(&[nmi as u8, (nmi >> 8) as u8, 0x00, 0xC0, irq as u8,
+                                        (irq >> 8) as u8]);
+
+    let mut image = vec![0x4E, 0x45, 0x53, 0x1A, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+    image.extend(prg_rom);
+
+    let mut cartridge: Cartridge = Default::default();
+    cartridge.read(&mut Cursor::new(image)).unwrap();
+
+    cartridge
+}
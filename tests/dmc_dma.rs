@@ -0,0 +1,98 @@
+//! Cycles taken from the CPU by DMC DMA are counted as cycles of the CPU
+
+extern crate lintel_nes;
+
+mod common;
+
+use lintel_nes::Machine;
+
+/// CPU cycles in a frame (with rendering disabled; no odd frames)
+const FRAME_CYCLES: f64 = 341.0 * 262.0 / 3.0;
+
+/// Address of the loop that is run once the DMC is playing
+const LOOP: u16 = 0xC015;
+
+/// Plays a long DMC sample at the fastest rate and then loops on a JMP (3 cycles)
+const PROGRAM: [u8; 24] = [
+    0x78,             // SEI
+    0xA9, 0x0F,       // LDA #$0F    ; Rate $F (54 cycles a bit); no IRQ or loop
+    0x8D, 0x10, 0x40, // STA $4010
+    0xA9, 0x00,       // LDA #$00    ; Sample at $C000
+    0x8D, 0x12, 0x40, // STA $4012
+    0xA9, 0xFF,       // LDA #$FF    ; 4081 bytes long (~60 frames)
+    0x8D, 0x13, 0x40, // STA $4013
+    0xA9, 0x10,       // LDA #$10    ; Start the DMC
+    0x8D, 0x15, 0x40, // STA $4015
+    0x4C, 0x15, 0xC0, // JMP $C015
+];
+
+fn machine() -> Machine {
+    let mut m = Machine::new();
+    m.insert(common::nrom(&PROGRAM, LOOP, LOOP));
+    m.reset();
+
+    while m.registers().pc != LOOP {
+        m.step_instruction();
+    }
+
+    m
+}
+
+#[test]
+fn step_instruction_counts_dma_cycles() {
+    let mut m = machine();
+    let start = m.cycles();
+
+    let mut total = 0;
+    let mut halted = 0;
+    for _ in 0..2000 {
+        let step = m.step_instruction();
+
+        // JMP (3 cycles) and, when halted for a DMA, 3 or 4 more cycles
+        assert!(step.cycles == 3 || step.cycles == 6 || step.cycles == 7,
+                "JMP took {} cycles",
+                step.cycles);
+
+        if step.cycles > 3 {
+            halted += 1;
+        }
+
+        total += step.cycles;
+    }
+
+    // A sample byte every 432 cycles
+    let expected = total as f64 / 432.0;
+    assert!((halted as f64 - expected).abs() <= 1.0,
+            "{} DMAs in {} cycles",
+            halted,
+            total);
+
+    assert_eq!(total, m.cycles().wrapping_sub(start));
+}
+
+#[test]
+fn run_frame_counts_dma_cycles() {
+    let mut m = machine();
+
+    // Start from the boundary of a frame
+    m.run_frame();
+
+    let start = m.cycles();
+    let frames = 10;
+
+    let mut total = 0;
+    for _ in 0..frames {
+        total += m.run_frame().step.cycles;
+    }
+
+    // A frame takes the same time with or without DMA; an instruction may run past the end
+    // of the frame (by up to 7 cycles)
+    let expected = FRAME_CYCLES * frames as f64;
+    assert!((total as f64 - expected).abs() <= 7.0,
+            "{} frames took {} cycles (expected {})",
+            frames,
+            total,
+            expected);
+
+    assert_eq!(total, m.cycles().wrapping_sub(start));
+}
@@ -0,0 +1,55 @@
+//! The interrupt reported for an instruction is the one whose vector was taken
+
+extern crate lintel_nes;
+
+mod common;
+
+use lintel_nes::Machine;
+
+/// Address of the BRK
+const BRK: u16 = 0xC005;
+
+/// Address of the NMI handler
+const NMI: u16 = 0xC00A;
+
+/// Address of the IRQ (and BRK) handler
+const IRQ: u16 = 0xC00B;
+
+/// Enables NMI and runs BRK in a loop; IRQ stays masked (I is set on reset)
+const PROGRAM: [u8; 12] = [
+    0xA9, 0x80,       // LDA #$80    ; NMI at the start of V-Blank
+    0x8D, 0x00, 0x20, // STA $2000
+    0x00, 0xEA,       // BRK         ; (padding byte)
+    0x4C, 0x00, 0xC0, // JMP $C000
+    0x40,             // RTI         ; NMI
+    0x40,             // RTI         ; IRQ and BRK
+];
+
+#[test]
+fn nmi_that_hijacks_brk_is_reported() {
+    let mut m = Machine::new();
+    m.insert(common::nrom(&PROGRAM, NMI, IRQ));
+    m.reset();
+
+    let mut nmis = 0;
+    let mut hijacked = 0;
+    while nmis < 120 {
+        let pc = m.registers().pc;
+        let step = m.step_instruction();
+        let vector = m.registers().pc;
+
+        assert_eq!(step.nmi, vector == NMI, "from ${:04X} to ${:04X}", pc, vector);
+        assert!(!step.irq, "from ${:04X} to ${:04X}", pc, vector);
+
+        if step.nmi {
+            nmis += 1;
+
+            if pc == BRK {
+                hijacked += 1;
+            }
+        }
+    }
+
+    // The BRK takes 7 of the 22 cycles of the loop
+    assert!(hijacked > 0, "no NMI hijacked a BRK");
+}
@@ -0,0 +1,80 @@
+//! Rewind goes back to the state of the machine as it was on the frame reached (the frames
+//! after the snapshot are run again with the input they were first run with)
+
+extern crate lintel_nes;
+
+mod common;
+
+use lintel_nes::Machine;
+
+/// Reads controller 1 over and over and keeps a running sum of the reads in $01 (so the
+/// state depends on the input of every frame)
+const PROGRAM: [u8; 30] = [
+    0xA9, 0x01,       // LDA #$01
+    0x8D, 0x16, 0x40, // STA $4016   ; Strobe
+    0xA9, 0x00,       // LDA #$00
+    0x8D, 0x16, 0x40, // STA $4016
+    0xA2, 0x08,       // LDX #$08
+    0xAD, 0x16, 0x40, // LDA $4016   ; (BNE here)
+    0x4A,             // LSR A
+    0x26, 0x00,       // ROL $00
+    0xCA,             // DEX
+    0xD0, 0xF7,       // BNE read
+    0xA5, 0x00,       // LDA $00
+    0x65, 0x01,       // ADC $01
+    0x85, 0x01,       // STA $01
+    0x4C, 0x00, 0xC0, // JMP $C000
+];
+
+const FRAMES: u32 = 40;
+
+/// Input of each frame (changes every frame)
+fn input(frame: u32) -> u8 {
+    (frame.wrapping_mul(37) ^ (frame >> 1)) as u8
+}
+
+fn machine() -> Machine {
+    let mut m = Machine::new();
+    m.insert(common::nrom(&PROGRAM, 0xC000, 0xC000));
+    m.reset();
+
+    m
+}
+
+/// Run a frame with the input of that frame (as a frontend would; set before the frame)
+fn run_frame(m: &mut Machine) {
+    let frame = m.frame();
+    m.set_controller_state(0, input(frame));
+    m.run_frame();
+}
+
+#[test]
+fn rewind_replays_input_of_each_frame() {
+    let mut m = machine();
+    m.enable_rewind(8, 1 << 24);
+
+    // Run to the first frame (so that frames start at V-Blank)
+    run_frame(&mut m);
+
+    // State at the start of each frame
+    let mut states = Vec::new();
+    while m.frame() < FRAMES {
+        states.push((m.frame(), m.save_state()));
+        run_frame(&mut m);
+    }
+
+    for &frames in &[1, 5, 11, 20] {
+        let reached = m.rewind(frames).unwrap();
+        assert_eq!(reached, FRAMES - frames);
+
+        let &(_, ref state) = states.iter().find(|&&(frame, _)| frame == reached).unwrap();
+        assert!(m.save_state() == *state,
+                "state after rewinding to frame {} differs",
+                reached);
+
+        // Run forward again (with the same input)
+        while m.frame() < FRAMES {
+            run_frame(&mut m);
+        }
+    }
+}
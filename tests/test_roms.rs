@@ -0,0 +1,89 @@
+//! Test ROMs that report their status at $6000 (blargg's CPU, PPU, and APU tests)
+//!
+//! The ROMs are not distributed with the emulator, so these tests are ignored by default.
+//! Place the ROMs in `tests/roms` (or the directory named by `LINTEL_TEST_ROMS`) with the
+//! layout of <https://github.com/christopherpow/nes-test-roms> and run them with
+//! `cargo test -- --ignored`; a test whose ROM is missing fails.
+
+extern crate lintel_nes;
+
+use lintel_nes::{Machine, TestStatus};
+use std::env;
+use std::path::PathBuf;
+
+/// Frames to run a ROM for before giving up (~2 minutes)
+const TIMEOUT: u32 = 60 * 120;
+
+fn roms() -> PathBuf {
+    match env::var("LINTEL_TEST_ROMS") {
+        Ok(dir) => PathBuf::from(dir),
+        Err(_) => PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests").join("roms"),
+    }
+}
+
+fn run(name: &str) {
+    let path = roms().join(name);
+    assert!(path.exists(), "{}: not found", path.display());
+
+    let mut m = Machine::new();
+    m.enable_test_monitor();
+    m.open(path.to_str().unwrap()).unwrap();
+    m.reset();
+
+    while m.frame() < TIMEOUT {
+        m.run_frame();
+
+        if let Some(&TestStatus::Finished(ref result)) = m.test_status() {
+            assert!(result.passed(),
+                    "{}: failed ({})\n{}",
+                    name,
+                    result.code,
+                    result.message);
+
+            return;
+        }
+    }
+
+    panic!("{}: timed out ({:?})", name, m.test_status());
+}
+
+macro_rules! test_roms {
+    ($($test:ident: $name:expr,)*) => {
+        $(
+            #[test]
+            #[ignore]
+            fn $test() {
+                run($name);
+            }
+        )*
+    };
+}
+
+// CPU
+test_roms! {
+    cpu_instr_official_only: "instr_test-v5/official_only.nes",
+    cpu_instr_misc: "instr_misc/instr_misc.nes",
+    cpu_instr_timing: "instr_timing/instr_timing.nes",
+    cpu_interrupts: "cpu_interrupts_v2/cpu_interrupts.nes",
+    cpu_reset_registers: "cpu_reset/registers.nes",
+    cpu_reset_ram_after_reset: "cpu_reset/ram_after_reset.nes",
+}
+
+// PPU
+test_roms! {
+    ppu_vbl_nmi: "ppu_vbl_nmi/ppu_vbl_nmi.nes",
+    ppu_open_bus: "ppu_open_bus/ppu_open_bus.nes",
+    ppu_oam_read: "oam_read/oam_read.nes",
+    ppu_oam_stress: "oam_stress/oam_stress.nes",
+}
+
+// APU
+test_roms! {
+    apu_test: "apu_test/apu_test.nes",
+    apu_reset_4015_cleared: "apu_reset/4015_cleared.nes",
+    apu_reset_4017_timing: "apu_reset/4017_timing.nes",
+    apu_reset_4017_written: "apu_reset/4017_written.nes",
+    apu_reset_irq_flag_cleared: "apu_reset/irq_flag_cleared.nes",
+    apu_reset_len_ctrs_enabled: "apu_reset/len_ctrs_enabled.nes",
+    apu_reset_works_immediately: "apu_reset/works_immediately.nes",
+}